@@ -1,6 +1,9 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
-use crate::{Attribute, Clear, Color, Event};
+use crate::{
+    Attribute, Clear, ClipboardSelection, Color, ColorSupport, Environment, Event, InputMode,
+    KeyboardEnhancementFlags, Mode, ModeStatus, TerminalIdentity,
+};
 
 /// A value that can be retrieved from the terminal.
 ///
@@ -14,6 +17,37 @@ pub enum Value {
     /// The application will wait indefinitely when `None`.
     /// It will wait for some duration if `Some(duration)` is given.
     Event(Option<Duration>),
+    /// Get the content of the clipboard.
+    ///
+    /// The terminal is queried with OSC 52, some terminals ask the user for permission first.
+    Clipboard(ClipboardSelection),
+    /// Get the colors that the terminal can display.
+    ///
+    /// This is detected from `COLORTERM`, `TERM` and the terminfo database,
+    /// the terminal itself isn't queried.
+    ColorSupport,
+    /// Get the default foreground and background colors of the terminal (OSC 10 and OSC 11),
+    /// e.g. to choose between a light and a dark theme.
+    DefaultColors,
+    /// Get the name, version and features of the terminal.
+    ///
+    /// The terminal is queried with XTVERSION and the secondary and primary device attributes (DA2, DA1).
+    TerminalIdentity,
+    /// Get the size of the window in pixels.
+    WindowPixelSize,
+    /// Get the size of a cell in pixels, e.g. to scale images to cells.
+    CellPixelSize,
+    /// Get the status of a DEC private mode (DECRQM),
+    /// e.g. to check whether the terminal supports a feature before enabling it.
+    ModeStatus(Mode),
+    /// Get a color of the palette by its index (OSC 4).
+    PaletteColor(u8),
+    /// Get the environment that the application runs in:
+    /// the terminal, multiplexers, SSH sessions and continuous integration services.
+    Environment,
+    /// Get how the terminal processes input and output,
+    /// see [Action::SetInputMode](enum.Action.html#variant.SetInputMode).
+    InputMode,
 }
 
 /// A result that is returned from a request for a [Value](enum.Value.html).
@@ -28,13 +62,43 @@ pub enum Retrieved {
     /// An event is returned.
     /// Timeout occurred if `None` is returned.
     Event(Option<Event>),
+    /// The content of the clipboard is returned.
+    /// `None` is returned if the terminal doesn't answer in time, e.g. because it doesn't support the query.
+    Clipboard(Option<String>),
+    /// The colors that the terminal can display are returned.
+    ColorSupport(ColorSupport),
+    /// The default colors are returned as `Color::Rgb`.
+    /// `None` is returned if the terminal doesn't answer in time, e.g. because it doesn't support the query.
+    DefaultColors {
+        foreground: Option<Color>,
+        background: Option<Color>,
+    },
+    /// The identity of the terminal is returned.
+    /// `None` is returned if the terminal doesn't answer in time.
+    TerminalIdentity(Option<TerminalIdentity>),
+    /// The size of the window is returned in pixels (width, height).
+    /// `None` is returned if neither the operating system nor the terminal report it.
+    WindowPixelSize(Option<(u16, u16)>),
+    /// The size of a cell is returned in pixels (width, height).
+    /// `None` is returned if neither the operating system nor the terminal report it.
+    CellPixelSize(Option<(u16, u16)>),
+    /// The status of the mode is returned.
+    /// `None` is returned if the terminal doesn't answer in time, e.g. because it doesn't support the query.
+    ModeStatus(Option<ModeStatus>),
+    /// The palette color is returned as `Color::Rgb`.
+    /// `None` is returned if the terminal doesn't answer in time, e.g. because it doesn't support the query.
+    PaletteColor(Option<Color>),
+    /// The environment is returned.
+    Environment(Environment),
+    /// The input mode is returned.
+    InputMode(InputMode),
 }
 
 /// An action that can be performed on the terminal.
 ///
 /// To perform an [Action](enum.Action.html) use [Terminal::act](struct.Terminal.html#method.act).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum Action {
     /// Moves the terminal cursor to the given position (column, row).
     MoveCursorTo(u16, u16),
@@ -54,11 +118,30 @@ pub enum Action {
     ScrollUp(u16),
     /// Scrolls the terminal screen a given number of rows down.
     ScrollDown(u16),
+    /// Disables line wrapping, text written beyond the right margin overwrites the last column.
+    DisableLineWrap,
+    /// Enables line wrapping, text written beyond the right margin continues on the next row.
+    EnableLineWrap,
+    /// Rings the terminal bell.
+    Bell,
+    /// Flashes the terminal screen, a visual alternative to the [Bell](enum.Action.html#variant.Bell).
+    ///
+    /// The screen is shown in reverse video for 100ms, so this flushes the pending batch
+    /// and blocks meanwhile, also when batched.
+    Flash,
 
     /// Enables raw mode.
     EnableRawMode,
     /// Disables raw mode.
     DisableRawMode,
+    /// Resets the terminal to its initial state (RIS).
+    ///
+    /// Raw mode and mouse event capturing are disabled as well.
+    ResetTerminal,
+    /// Resets the terminal modes to their defaults, without clearing the screen (DECSTR).
+    ///
+    /// Raw mode and mouse event capturing are disabled as well.
+    SoftReset,
     /// Switches to alternate screen.
     EnterAlternateScreen,
     /// Switches back to the main screen.
@@ -68,15 +151,102 @@ pub enum Action {
     EnableMouseCapture,
     /// Disables mouse event capturing.
     DisableMouseCapture,
+    /// Enables bracketed paste, pasted text is reported as one
+    /// [Event::Paste](enum.Event.html#variant.Paste) instead of separate key events.
+    EnableBracketedPaste,
+    /// Disables bracketed paste.
+    DisableBracketedPaste,
+    /// Enables reporting of [Event::FocusGained](enum.Event.html#variant.FocusGained)
+    /// and [Event::FocusLost](enum.Event.html#variant.FocusLost).
+    EnableFocusChange,
+    /// Disables reporting of focus changes.
+    DisableFocusChange,
+    /// Begins a synchronized update, the terminal holds back rendering until
+    /// [Action::EndSynchronizedUpdate](enum.Action.html#variant.EndSynchronizedUpdate)
+    /// so that the update is presented at once.
+    ///
+    /// See [Terminal::set_synchronized_flush](struct.Terminal.html#method.set_synchronized_flush)
+    /// to wrap every flushed batch in a synchronized update.
+    BeginSynchronizedUpdate,
+    /// Ends a synchronized update.
+    EndSynchronizedUpdate,
+    /// Pushes keyboard enhancement flags of the
+    /// [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/)
+    /// onto the terminal's stack.
+    ///
+    /// This enables e.g. unambiguous escape codes and key release events,
+    /// terminals that don't support the protocol ignore it.
+    PushKeyboardEnhancementFlags(KeyboardEnhancementFlags),
+    /// Pops the keyboard enhancement flags that were pushed last.
+    PopKeyboardEnhancementFlags,
+    /// Enables xterm's modifyOtherKeys (level 2).
+    ///
+    /// Key combinations that have no legacy encoding,
+    /// such as Ctrl + Shift + letter and Ctrl + digit, are reported with all modifiers.
+    EnableModifyOtherKeys,
+    /// Disables xterm's modifyOtherKeys.
+    DisableModifyOtherKeys,
 
     /// Sets the the foreground color.
     SetForegroundColor(Color),
     /// Sets the the background color.
     SetBackgroundColor(Color),
+    /// Sets the color of underlines, independent of the foreground color.
+    ///
+    /// `Color::Reset` underlines with the foreground color again.
+    SetUnderlineColor(Color),
     /// Sets an attribute.
     SetAttribute(Attribute),
     /// Resets the colors back to default.
     ResetColor,
+    /// Starts a hyperlink, text that is written until
+    /// [Action::EndHyperlink](enum.Action.html#variant.EndHyperlink) links to `uri`.
+    ///
+    /// Text with the same `id` is highlighted as one link, e.g. when it is spread over several lines.
+    /// Terminals that don't support hyperlinks show plain text.
+    ///
+    /// Bytes of `uri` outside of the printable ASCII range are percent-encoded,
+    /// control characters, `:` and `;` are removed from `id`.
+    BeginHyperlink { uri: String, id: Option<String> },
+    /// Ends the current hyperlink.
+    EndHyperlink,
+    /// Sets the content of the clipboard (OSC 52).
+    ///
+    /// This also works over SSH and inside tmux (with `set-clipboard` enabled).
+    SetClipboard {
+        selection: ClipboardSelection,
+        content: String,
+    },
+    /// Shows a desktop notification (OSC 777 or OSC 9, depending on the terminal).
+    ///
+    /// Terminals that don't support notifications ignore it.
+    Notify { title: String, body: String },
+    /// Marks the start of a prompt (OSC 133).
+    ///
+    /// Prompt marks let the terminal jump between prompts and select command output.
+    MarkPromptStart,
+    /// Marks the end of the prompt and the start of the command input (OSC 133).
+    MarkCommandStart,
+    /// Marks the start of the command output (OSC 133).
+    MarkOutputStart,
+    /// Marks the end of the command output with the exit code of the command, if any (OSC 133).
+    MarkCommandFinished(Option<i32>),
+    /// Reports the working directory to the terminal (OSC 7).
+    ///
+    /// Terminals open new tabs and windows in the reported directory.
+    /// A relative path is resolved against the current directory of the process.
+    ReportWorkingDirectory(PathBuf),
+    /// Changes a color of the palette, e.g. to remap the 16 base colors (OSC 4).
+    ///
+    /// The color has to be a `Color::Rgb`, `Color::Reset` resets the palette color to its default.
+    SetPaletteColor(u8, Color),
+    /// Resets all colors of the palette to their defaults (OSC 104).
+    ResetPalette,
+    /// Sets how the terminal processes input and output, e.g. to read single keys with cbreak mode.
+    ///
+    /// The other terminal settings are left as they are.
+    /// [Action::DisableRawMode](enum.Action.html#variant.DisableRawMode) doesn't undo this action.
+    SetInputMode(InputMode),
 }
 
 impl From<Action> for String {
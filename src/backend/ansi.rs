@@ -0,0 +1,365 @@
+//! Escape sequences that are written directly to the buffer.
+//!
+//! Not every backend library offers an API for the following terminal features.
+//! Backends can use these sequences instead, so that all of them behave the same.
+
+use std::{env, io, path::Path, time::Duration};
+
+use crate::{backend::base64, ClipboardSelection, Color, KeyboardEnhancementFlags, Mode};
+
+/// The time to wait for the terminal to answer a query.
+///
+/// Terminals that don't support a query don't answer at all.
+pub(crate) const QUERY_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// A sequence of escape codes to disable line wrapping at the right margin (DECAWM).
+pub(crate) const DISABLE_LINE_WRAP: &str = "\x1B[?7l";
+
+/// A sequence of escape codes to enable line wrapping at the right margin (DECAWM).
+pub(crate) const ENABLE_LINE_WRAP: &str = "\x1B[?7h";
+
+/// The bell character (BEL), most terminals play an audible alert.
+pub(crate) const BELL: &str = "\x07";
+
+/// A sequence of escape codes to switch the screen to reverse video (DECSCNM).
+/// Together with `DISABLE_REVERSE_SCREEN` it is used as visual bell.
+pub(crate) const ENABLE_REVERSE_SCREEN: &str = "\x1B[?5h";
+
+/// A sequence of escape codes to switch the screen back to normal video (DECSCNM).
+pub(crate) const DISABLE_REVERSE_SCREEN: &str = "\x1B[?5l";
+
+/// The time the screen stays in reverse video when flashing.
+pub(crate) const FLASH_DURATION: Duration = Duration::from_millis(100);
+
+/// A sequence of escape codes to reset the terminal to its initial state (RIS).
+pub(crate) const RESET_TERMINAL: &str = "\x1Bc";
+
+/// A sequence of escape codes to reset the terminal modes to their defaults (DECSTR).
+pub(crate) const SOFT_RESET: &str = "\x1B[!p";
+
+/// A sequence of escape codes to enable bracketed paste.
+pub(crate) const ENABLE_BRACKETED_PASTE: &str = "\x1B[?2004h";
+
+/// A sequence of escape codes to disable bracketed paste.
+pub(crate) const DISABLE_BRACKETED_PASTE: &str = "\x1B[?2004l";
+
+/// The sequence the terminal sends before pasted text.
+pub(crate) const BRACKETED_PASTE_START: &str = "\x1B[200~";
+
+/// The sequence the terminal sends after pasted text.
+pub(crate) const BRACKETED_PASTE_END: &str = "\x1B[201~";
+
+/// A sequence of escape codes to enable focus change reporting.
+pub(crate) const ENABLE_FOCUS_CHANGE: &str = "\x1B[?1004h";
+
+/// A sequence of escape codes to disable focus change reporting.
+pub(crate) const DISABLE_FOCUS_CHANGE: &str = "\x1B[?1004l";
+
+/// Returns a sequence of escape codes that pushes keyboard enhancement flags
+/// onto the stack of the kitty keyboard protocol.
+pub(crate) fn push_keyboard_enhancement_flags(flags: KeyboardEnhancementFlags) -> String {
+    format!("\x1B[>{}u", flags.bits())
+}
+
+/// A sequence of escape codes to pop one entry from the stack of the kitty keyboard protocol.
+pub(crate) const POP_KEYBOARD_ENHANCEMENT_FLAGS: &str = "\x1B[<1u";
+
+/// A sequence of escape codes to set xterm's modifyOtherKeys to level 2,
+/// which reports all key and modifier combinations.
+pub(crate) const ENABLE_MODIFY_OTHER_KEYS: &str = "\x1B[>4;2m";
+
+/// A sequence of escape codes to disable xterm's modifyOtherKeys.
+pub(crate) const DISABLE_MODIFY_OTHER_KEYS: &str = "\x1B[>4;0m";
+
+/// A sequence of escape codes to begin a synchronized update (DEC mode 2026).
+pub(crate) const BEGIN_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026h";
+
+/// A sequence of escape codes to end a synchronized update (DEC mode 2026).
+pub(crate) const END_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026l";
+
+/// A sequence of escape codes to double underline the text.
+pub(crate) const DOUBLE_UNDERLINED: &str = "\x1B[4:2m";
+
+/// A sequence of escape codes to underline the text with a curly line.
+pub(crate) const UNDERCURLED: &str = "\x1B[4:3m";
+
+/// A sequence of escape codes to underline the text with a dotted line.
+pub(crate) const UNDERDOTTED: &str = "\x1B[4:4m";
+
+/// A sequence of escape codes to underline the text with a dashed line.
+pub(crate) const UNDERDASHED: &str = "\x1B[4:5m";
+
+/// Returns a sequence of escape codes that sets the underline color (SGR 58),
+/// `Color::Reset` uses the foreground color again (SGR 59).
+pub(crate) fn set_underline_color(color: Color) -> String {
+    let index = match color {
+        Color::Reset => return String::from("\x1B[59m"),
+        Color::Rgb(r, g, b) => return format!("\x1B[58;2;{};{};{}m", r, g, b),
+        Color::AnsiValue(index) => index,
+        Color::Black => 0,
+        Color::DarkRed => 1,
+        Color::DarkGreen => 2,
+        Color::DarkYellow => 3,
+        Color::DarkBlue => 4,
+        Color::DarkMagenta => 5,
+        Color::DarkCyan => 6,
+        Color::Grey => 7,
+        Color::DarkGrey => 8,
+        Color::Red => 9,
+        Color::Green => 10,
+        Color::Yellow => 11,
+        Color::Blue => 12,
+        Color::Magenta => 13,
+        Color::Cyan => 14,
+        Color::White => 15,
+    };
+
+    format!("\x1B[58;5;{}m", index)
+}
+
+/// Returns a sequence of escape codes that starts a hyperlink (OSC 8).
+///
+/// Text written until the hyperlink is ended links to the given URI,
+/// cells with the same id are underlined together on hover.
+pub(crate) fn begin_hyperlink(uri: &str, id: Option<&str>) -> String {
+    // Bytes outside of the printable ASCII range could end the sequence early.
+    let mut encoded_uri = String::with_capacity(uri.len());
+
+    for byte in uri.bytes() {
+        match byte {
+            32..=126 => encoded_uri.push(byte as char),
+            byte => encoded_uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    match id {
+        Some(id) => {
+            // The id can't contain the separators of the parameters.
+            let id: String = id
+                .chars()
+                .filter(|c| !c.is_control() && *c != ':' && *c != ';')
+                .collect();
+
+            format!("\x1B]8;id={};{}\x1B\\", id, encoded_uri)
+        }
+        None => format!("\x1B]8;;{}\x1B\\", encoded_uri),
+    }
+}
+
+/// A sequence of escape codes to end a hyperlink (OSC 8).
+pub(crate) const END_HYPERLINK: &str = "\x1B]8;;\x1B\\";
+
+fn clipboard_selection(selection: ClipboardSelection) -> char {
+    match selection {
+        ClipboardSelection::Clipboard => 'c',
+        ClipboardSelection::Primary => 'p',
+    }
+}
+
+/// Returns a sequence of escape codes that sets the content of the clipboard (OSC 52).
+pub(crate) fn set_clipboard(selection: ClipboardSelection, content: &str) -> String {
+    format!(
+        "\x1B]52;{};{}\x1B\\",
+        clipboard_selection(selection),
+        base64::encode(content.as_bytes())
+    )
+}
+
+/// Returns a sequence of escape codes that queries the content of the clipboard (OSC 52).
+pub(crate) fn query_clipboard(selection: ClipboardSelection) -> String {
+    format!("\x1B]52;{};?\x1B\\", clipboard_selection(selection))
+}
+
+/// A sequence of escape codes that queries the cursor position (DSR 6).
+pub(crate) const QUERY_CURSOR_POSITION: &str = "\x1B[6n";
+
+/// A sequence of escape codes that queries the default foreground color (OSC 10).
+pub(crate) const QUERY_FOREGROUND_COLOR: &str = "\x1B]10;?\x1B\\";
+
+/// A sequence of escape codes that queries the default background color (OSC 11).
+pub(crate) const QUERY_BACKGROUND_COLOR: &str = "\x1B]11;?\x1B\\";
+
+/// A sequence of escape codes that queries the name and version of the terminal (XTVERSION),
+/// the secondary device attributes (DA2) and the primary device attributes (DA1).
+///
+/// Every terminal answers DA1, its response comes last and ends the responses to this query.
+pub(crate) const QUERY_TERMINAL_IDENTITY: &str = "\x1B[>0q\x1B[>c\x1B[c";
+
+/// A sequence of escape codes that queries the size of the window in pixels (`CSI 14 t`).
+pub(crate) const QUERY_WINDOW_PIXEL_SIZE: &str = "\x1B[14t";
+
+/// A sequence of escape codes that queries the size of a cell in pixels (`CSI 16 t`).
+pub(crate) const QUERY_CELL_PIXEL_SIZE: &str = "\x1B[16t";
+
+/// Returns the number of a DEC private mode.
+pub(crate) fn mode_number(mode: Mode) -> u16 {
+    match mode {
+        Mode::LineWrap => 7,
+        Mode::CursorVisible => 25,
+        Mode::MouseCapture => 1002,
+        Mode::FocusChange => 1004,
+        Mode::SgrMouse => 1006,
+        Mode::AlternateScreen => 1049,
+        Mode::BracketedPaste => 2004,
+        Mode::SynchronizedUpdate => 2026,
+        Mode::Other(number) => number,
+    }
+}
+
+/// Returns a sequence of escape codes that queries the status of a DEC private mode (DECRQM).
+pub(crate) fn query_mode(mode: Mode) -> String {
+    format!("\x1B[?{}$p", mode_number(mode))
+}
+
+/// Returns a sequence of escape codes that changes a color of the palette (OSC 4),
+/// or resets it with `Color::Reset` (OSC 104).
+///
+/// Returns `None` for colors without RGB value.
+pub(crate) fn set_palette_color(index: u8, color: Color) -> Option<String> {
+    match color {
+        Color::Rgb(r, g, b) => Some(format!(
+            "\x1B]4;{};rgb:{:02x}/{:02x}/{:02x}\x1B\\",
+            index, r, g, b
+        )),
+        Color::Reset => Some(format!("\x1B]104;{}\x1B\\", index)),
+        _ => None,
+    }
+}
+
+/// A sequence of escape codes that resets all colors of the palette (OSC 104).
+pub(crate) const RESET_PALETTE: &str = "\x1B]104\x1B\\";
+
+/// Returns a sequence of escape codes that queries a color of the palette (OSC 4).
+pub(crate) fn query_palette_color(index: u8) -> String {
+    format!("\x1B]4;{};?\x1B\\", index)
+}
+
+/// Returns a sequence of escape codes that shows a desktop notification.
+///
+/// OSC 777 is used by VTE based terminals, rxvt and foot, other terminals use OSC 9.
+pub(crate) fn notify(title: &str, body: &str) -> String {
+    // Control characters would end the sequence early.
+    let strip = |text: &str| -> String { text.chars().filter(|c| !c.is_control()).collect() };
+
+    let term = env::var("TERM").unwrap_or_default();
+
+    if env::var_os("VTE_VERSION").is_some() || term.starts_with("rxvt") || term.starts_with("foot")
+    {
+        // The title can't contain the separator.
+        format!(
+            "\x1B]777;notify;{};{}\x1B\\",
+            strip(title).replace(';', ","),
+            strip(body)
+        )
+    } else {
+        format!("\x1B]9;{}: {}\x1B\\", strip(title), strip(body))
+    }
+}
+
+/// A sequence of escape codes to mark the start of a prompt (OSC 133).
+pub(crate) const MARK_PROMPT_START: &str = "\x1B]133;A\x1B\\";
+
+/// A sequence of escape codes to mark the start of the command input, after the prompt (OSC 133).
+pub(crate) const MARK_COMMAND_START: &str = "\x1B]133;B\x1B\\";
+
+/// A sequence of escape codes to mark the start of the command output (OSC 133).
+pub(crate) const MARK_OUTPUT_START: &str = "\x1B]133;C\x1B\\";
+
+/// Returns a sequence of escape codes that marks the end of a command with its exit code (OSC 133).
+pub(crate) fn mark_command_finished(exit_code: Option<i32>) -> String {
+    match exit_code {
+        Some(exit_code) => format!("\x1B]133;D;{}\x1B\\", exit_code),
+        None => String::from("\x1B]133;D\x1B\\"),
+    }
+}
+
+/// Returns a sequence of escape codes that reports the working directory as `file://` URL (OSC 7).
+///
+/// Relative paths are resolved against the current directory of the process.
+pub(crate) fn report_working_directory(path: &Path) -> io::Result<String> {
+    if path.is_relative() {
+        return report_working_directory(&env::current_dir()?.join(path));
+    }
+
+    Ok(working_directory_url(&hostname(), path))
+}
+
+fn working_directory_url(hostname: &str, path: &Path) -> String {
+    let mut url = format!("\x1B]7;file://{}", hostname);
+
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = format!("/{}", path.to_string_lossy().replace('\\', "/")).into_bytes();
+
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' => {
+                url.push(byte as char)
+            }
+            byte => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    url.push_str("\x1B\\");
+    url
+}
+
+/// Returns the host name, terminals only accept working directories on the local host.
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut name = [0u8; 256];
+
+    if unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) } != 0 {
+        return String::new();
+    }
+
+    let length = name
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..length]).into_owned()
+}
+
+/// Returns the host name, terminals only accept working directories on the local host.
+#[cfg(not(unix))]
+fn hostname() -> String {
+    env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{begin_hyperlink, report_working_directory, working_directory_url};
+
+    #[test]
+    fn test_begin_hyperlink() {
+        assert_eq!(
+            begin_hyperlink("https://example.com/a b", None),
+            "\x1B]8;;https://example.com/a b\x1B\\"
+        );
+        assert_eq!(
+            begin_hyperlink("https://example.com/\x1B\\\x07\u{9C}ä", Some("x")),
+            "\x1B]8;id=x;https://example.com/%1B\\%07%C2%9C%C3%A4\x1B\\"
+        );
+        assert_eq!(
+            begin_hyperlink("https://example.com", Some("a:b;c\x1Bd")),
+            "\x1B]8;id=abcd;https://example.com\x1B\\"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_working_directory_url() {
+        assert_eq!(
+            working_directory_url("host", Path::new("/home/user/my dir/ä%")),
+            "\x1B]7;file://host/home/user/my%20dir/%C3%A4%25\x1B\\"
+        );
+
+        let url = report_working_directory(Path::new("src")).unwrap();
+        assert!(url.starts_with("\x1B]7;file://"));
+        assert!(url.ends_with("/src\x1B\\"));
+        assert!(!url.contains("file://src"));
+    }
+}
@@ -0,0 +1,73 @@
+//! Base64 encoding (RFC 4648) of data that is sent to and received from the terminal.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the given bytes, with padding.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (index, byte)| {
+            value | u32::from(*byte) << (16 - 8 * index)
+        });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (value >> (18 - 6 * index)) & 0b11_1111;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Decodes the given text, padding is optional.
+///
+/// Returns `None` if the text is not valid base64.
+pub(crate) fn decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+
+    for chunk in text.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+
+        let mut value = 0u32;
+
+        for (index, byte) in chunk.iter().enumerate() {
+            let sextet = ALPHABET.iter().position(|c| c == byte)? as u32;
+            value |= sextet << (18 - 6 * index);
+        }
+
+        for index in 0..chunk.len() - 1 {
+            decoded.push((value >> (16 - 8 * index)) as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+
+    #[test]
+    fn test_encode_decode() {
+        for (bytes, text) in &[
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(bytes), *text);
+            assert_eq!(decode(text).as_deref(), Some(*bytes));
+        }
+
+        assert_eq!(decode("Zm9v!"), None);
+    }
+}
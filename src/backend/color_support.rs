@@ -0,0 +1,213 @@
+//! Detection of the colors that the terminal can display.
+//!
+//! Terminals don't answer a query for this, the environment and the terminfo database are used instead.
+
+use std::{env, fs, path::PathBuf};
+
+use crate::ColorSupport;
+
+/// The index of the `colors` capability in the numbers section of a terminfo entry.
+const COLORS_CAPABILITY: usize = 13;
+
+/// Detects the colors of the terminal from `COLORTERM`, `TERM` and the terminfo entry of `TERM`.
+pub(crate) fn detect() -> ColorSupport {
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+
+    from_environment(&colorterm, &term, || terminfo_colors(&term))
+}
+
+/// Maps the number of colors that curses reports.
+pub(crate) fn from_colors(colors: i32) -> ColorSupport {
+    match colors {
+        _ if colors >= 1 << 24 => ColorSupport::TrueColor,
+        _ if colors >= 256 => ColorSupport::Ansi256,
+        _ if colors >= 8 => ColorSupport::Ansi16,
+        _ => ColorSupport::Monochrome,
+    }
+}
+
+fn from_environment(
+    colorterm: &str,
+    term: &str,
+    terminfo_colors: impl FnOnce() -> Option<i32>,
+) -> ColorSupport {
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorSupport::TrueColor;
+    }
+
+    if term == "dumb" {
+        return ColorSupport::Monochrome;
+    }
+
+    // Windows doesn't set `TERM`, its console knows at least the basic colors.
+    if term.is_empty() {
+        return if cfg!(windows) {
+            ColorSupport::Ansi16
+        } else {
+            ColorSupport::Monochrome
+        };
+    }
+
+    if term.ends_with("-direct") || term.contains("truecolor") {
+        return ColorSupport::TrueColor;
+    }
+
+    match terminfo_colors() {
+        Some(colors) => from_colors(colors),
+        None if term.contains("256color") => ColorSupport::Ansi256,
+        // Without a terminfo entry, most terminals still know the basic colors.
+        None => ColorSupport::Ansi16,
+    }
+}
+
+/// Reads the `colors` capability from the compiled terminfo entry of `term`.
+fn terminfo_colors(term: &str) -> Option<i32> {
+    let first = term.chars().next()?;
+
+    terminfo_directories().into_iter().find_map(|directory| {
+        // macOS names the subdirectories after the hexadecimal value of the first character.
+        let entry = fs::read(directory.join(first.to_string()).join(term))
+            .or_else(|_| fs::read(directory.join(format!("{:x}", first as u32)).join(term)))
+            .ok()?;
+
+        parse_colors(&entry)
+    })
+}
+
+/// The directories that are searched for terminfo entries, in the order of ncurses.
+fn terminfo_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+
+    if let Some(directory) = env::var_os("TERMINFO") {
+        directories.push(PathBuf::from(directory));
+    }
+
+    if let Some(home) = env::var_os("HOME") {
+        directories.push(PathBuf::from(home).join(".terminfo"));
+    }
+
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        directories.extend(
+            list.split(':')
+                .filter(|directory| !directory.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+
+    for directory in &[
+        "/etc/terminfo",
+        "/lib/terminfo",
+        "/usr/share/terminfo",
+        "/usr/lib/terminfo",
+    ] {
+        directories.push(PathBuf::from(directory));
+    }
+
+    directories
+}
+
+/// Parses the `colors` capability of a compiled terminfo entry, see `term(5)`.
+fn parse_colors(entry: &[u8]) -> Option<i32> {
+    let short = |index: usize| -> Option<usize> {
+        let bytes = entry.get(index * 2..index * 2 + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+
+    // The extended format stores numbers with 32 instead of 16 bits.
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+
+    let (names_size, booleans_count, numbers_count) = (short(1)?, short(2)?, short(3)?);
+
+    if numbers_count <= COLORS_CAPABILITY {
+        return None;
+    }
+
+    // The numbers section starts at an even offset.
+    let mut offset = 12 + names_size + booleans_count;
+    offset += offset % 2;
+    offset += COLORS_CAPABILITY * number_size;
+
+    let bytes = entry.get(offset..offset + number_size)?;
+
+    let colors = match number_size {
+        2 => i32::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+
+    // Absent capabilities are stored as negative numbers.
+    if colors < 0 {
+        None
+    } else {
+        Some(colors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_environment, parse_colors};
+    use crate::ColorSupport;
+
+    fn entry(magic: u16, colors: &[u8]) -> Vec<u8> {
+        let number_size = if magic == 0o432 { 2 } else { 4 };
+        let names = b"test|terminal\0";
+        let booleans = [0u8; 3];
+
+        let mut entry = Vec::new();
+
+        for short in &[magic, names.len() as u16, booleans.len() as u16, 14, 0, 0] {
+            entry.extend_from_slice(&short.to_le_bytes());
+        }
+
+        entry.extend_from_slice(names);
+        entry.extend_from_slice(&booleans);
+        entry.push(0);
+        entry.extend(vec![0xFF; 13 * number_size]);
+        entry.extend_from_slice(colors);
+        entry
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        assert_eq!(parse_colors(&entry(0o432, &8i16.to_le_bytes())), Some(8));
+        assert_eq!(parse_colors(&entry(0o432, &(-1i16).to_le_bytes())), None);
+        assert_eq!(
+            parse_colors(&entry(0o1036, &(1i32 << 24).to_le_bytes())),
+            Some(1 << 24)
+        );
+        assert_eq!(parse_colors(&entry(0o432, &[])), None);
+        assert_eq!(parse_colors(b"not terminfo"), None);
+    }
+
+    #[test]
+    fn test_from_environment() {
+        for (colorterm, term, colors, expected) in &[
+            (
+                "truecolor",
+                "xterm-256color",
+                Some(256),
+                ColorSupport::TrueColor,
+            ),
+            ("24bit", "screen", Some(8), ColorSupport::TrueColor),
+            ("", "xterm-direct", None, ColorSupport::TrueColor),
+            ("", "xterm-256color", None, ColorSupport::Ansi256),
+            ("", "tmux-256color", Some(256), ColorSupport::Ansi256),
+            ("", "linux", Some(8), ColorSupport::Ansi16),
+            ("", "screen", None, ColorSupport::Ansi16),
+            ("", "vt220", Some(0), ColorSupport::Monochrome),
+            ("", "dumb", Some(8), ColorSupport::Monochrome),
+        ] {
+            assert_eq!(
+                from_environment(colorterm, term, || *colors),
+                *expected,
+                "COLORTERM={} TERM={}",
+                colorterm,
+                term
+            );
+        }
+    }
+}
@@ -9,3 +9,6 @@ pub(crate) const ENABLE_MOUSE_CAPTURE: &str = "\x1B[?1002h";
 /// A sequence of escape codes to disable terminal mouse support.
 /// We use this directly instead of using `MouseTerminal` from termion.
 pub(crate) const DISABLE_MOUSE_CAPTURE: &str = "\x1B[?1002l";
+
+/// The time in milliseconds to wait for the next character of pasted text.
+pub(crate) const PASTE_TIMEOUT: i32 = 100;
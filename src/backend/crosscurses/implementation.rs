@@ -1,15 +1,24 @@
 use crate::backend::crosscurses::constants;
+use crate::backend::query;
 use crate::{
     backend::{
+        ansi, color_support,
         crosscurses::{current_style::CurrentStyle, mapping::find_closest},
-        Backend,
+        environment, input, input_mode, Backend,
     },
-    error, Action, Attribute, Clear, Color, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton,
-    Retrieved, Value,
+    error, Action, Attribute, Clear, Color, Event, InputMode, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, Retrieved, Value,
 };
 use crosscurses::{ToChtype, Window, COLORS};
 use std::{
-    collections::HashMap, ffi::CStr, fs::File, io, io::Write, os::unix::io::IntoRawFd, result,
+    collections::{HashMap, VecDeque},
+    ffi::CStr,
+    fs::File,
+    io,
+    io::{IsTerminal, Write},
+    mem,
+    os::unix::io::{AsRawFd, IntoRawFd},
+    result,
     sync::RwLock,
 };
 
@@ -35,14 +44,15 @@ struct InputCache {
     // so we have to save it with the mouse down event
     last_mouse_button: Option<MouseButton>,
 
-    stored_event: Option<Event>,
+    // Events that are returned before new input is read.
+    stored_events: VecDeque<Event>,
 }
 
 pub struct BackendImpl<W: Write> {
     buffer: W,
     // We can batch commands in the crosscurses window.
     // The moment we call `refresh` these are executed.
-    window: crosscurses::Window,
+    pub(crate) window: crosscurses::Window,
 
     // The cache needed to parse input.
     input_cache: RwLock<InputCache>,
@@ -56,6 +66,9 @@ pub struct BackendImpl<W: Write> {
 
     // This is necessary to know the style that is currently set.
     current_style: CurrentStyle,
+
+    // Whether the screen is written to a terminal, it is not written to the buffer.
+    output_is_tty: bool,
 }
 
 impl<W: Write> BackendImpl<W> {
@@ -80,15 +93,33 @@ impl<W: Write> BackendImpl<W> {
         Ok(())
     }
 
-    /// Updates the stored event.
+    /// Writes an escape sequence that applies to the cursor position,
+    /// after the window content has been written to the terminal.
+    fn write_at_cursor(&mut self, sequence: &str) -> error::Result<()> {
+        self.window.refresh();
+        self.buffer.write_all(sequence.as_bytes())?;
+        self.buffer.flush()?;
+        Ok(())
+    }
+
+    /// Stores an event, stored events are returned in order before new input is read.
     pub(crate) fn update_stored_event(&self, btn: Event) {
         let mut lock = self.input_cache.write().unwrap();
-        lock.stored_event = Some(btn);
+        lock.stored_events.push_back(btn);
     }
 
     /// Tries to read event from temporary cache.
     fn try_take(&self) -> Option<Event> {
-        self.input_cache.write().unwrap().stored_event.take()
+        self.input_cache.write().unwrap().stored_events.pop_front()
+    }
+
+    /// Queries the terminal, returns `None` if it doesn't answer in time.
+    fn query<T>(
+        &self,
+        query: &str,
+        parse: impl FnMut(&[u8]) -> Option<T>,
+    ) -> error::Result<Option<T>> {
+        query::query(query, parse, |event| self.update_stored_event(event))
     }
 
     /// Updates the last used button with a new button.
@@ -152,6 +183,43 @@ fn init_stdout_window() -> Window {
     crosscurses::initscr()
 }
 
+/// Converts a color intensity (0-255) to the range of curses (0-1000).
+fn to_curses_intensity(intensity: u8) -> i16 {
+    ((i32::from(intensity) * 1000 + 127) / 255) as i16
+}
+
+/// Converts a color intensity of curses (0-1000) to the range 0-255.
+fn from_curses_intensity(intensity: i16) -> u8 {
+    ((i32::from(intensity) * 255 + 500) / 1000) as u8
+}
+
+/// Returns the size of the window in pixels (width, height), if the terminal reports it.
+#[cfg(unix)]
+fn window_pixel_size() -> Option<(u16, u16)> {
+    // curses doesn't know the pixel size, it is read from the terminal device directly.
+    let tty = File::open("/dev/tty").ok()?;
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+
+    if unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == -1 {
+        return None;
+    }
+
+    Some((size.ws_xpixel, size.ws_ypixel)).filter(|(width, height)| *width > 0 && *height > 0)
+}
+
+#[cfg(not(unix))]
+fn window_pixel_size() -> Option<(u16, u16)> {
+    None
+}
+
+/// Returns `true` if the screen is written to stdout instead of `/dev/tty`.
+fn screen_on_stdout() -> bool {
+    cfg!(windows)
+        || std::env::var("TERM")
+            .map(|var| var.is_empty())
+            .unwrap_or(false)
+}
+
 #[cfg(unix)]
 fn init_custom_window() -> Window {
     // By default crosscurses use stdout.
@@ -166,11 +234,7 @@ fn init_custom_window() -> Window {
         )
     };
 
-    if cfg!(unix)
-        && std::env::var("TERM")
-            .map(|var| var.is_empty())
-            .unwrap_or(false)
-    {
+    if screen_on_stdout() {
         init_stdout_window()
     } else {
         // Create screen pointer which we will be using for this backend.
@@ -215,6 +279,7 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             key_codes: initialize_keymap(),
             current_style: CurrentStyle::new(),
             buffer,
+            output_is_tty: !screen_on_stdout() || io::stdout().is_terminal(),
         }
     }
 
@@ -254,6 +319,20 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             Action::SetTerminalSize(cols, rows) => {
                 crosscurses::resize_term(rows as i32, cols as i32);
             }
+            Action::DisableLineWrap => {
+                self.buffer.write_all(ansi::DISABLE_LINE_WRAP.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::EnableLineWrap => {
+                self.buffer.write_all(ansi::ENABLE_LINE_WRAP.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::Bell => {
+                check!(crosscurses::beep());
+            }
+            Action::Flash => {
+                check!(crosscurses::flash());
+            }
             Action::EnableRawMode => {
                 check!(crosscurses::noecho());
                 check!(crosscurses::raw());
@@ -264,6 +343,54 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 check!(crosscurses::noraw());
                 check!(crosscurses::nl());
             }
+            Action::SetInputMode(mode) => {
+                // curses only knows line input with signals, cbreak mode and raw mode,
+                // output processing is left to `nl` and `nonl`.
+                if !mode.contains(InputMode::OUTPUT_PROCESSING)
+                    || mode.contains(InputMode::CANONICAL) && !mode.contains(InputMode::SIGNALS)
+                {
+                    return Err(error::ErrorKind::ActionNotSupported(String::from(action)));
+                }
+
+                if mode.contains(InputMode::ECHO) {
+                    check!(crosscurses::echo());
+                } else {
+                    check!(crosscurses::noecho());
+                }
+
+                if mode.contains(InputMode::CANONICAL) {
+                    check!(crosscurses::noraw());
+                    check!(crosscurses::nocbreak());
+                } else if mode.contains(InputMode::SIGNALS) {
+                    check!(crosscurses::noraw());
+                    check!(crosscurses::cbreak());
+                } else {
+                    check!(crosscurses::raw());
+                }
+            }
+            Action::ResetTerminal | Action::SoftReset => {
+                // Not every terminal disables mouse event capturing on a soft reset.
+                self.buffer
+                    .write_all(constants::DISABLE_MOUSE_CAPTURE.as_bytes())?;
+
+                let sequence = match action {
+                    Action::ResetTerminal => ansi::RESET_TERMINAL,
+                    _ => ansi::SOFT_RESET,
+                };
+                self.buffer.write_all(sequence.as_bytes())?;
+                self.buffer.flush()?;
+
+                check!(crosscurses::echo());
+                check!(crosscurses::noraw());
+                check!(crosscurses::nl());
+
+                self.current_style = CurrentStyle::new();
+                check!(self.window.attrset(crosscurses::Attribute::Normal));
+
+                // The screen contents ncurses knows about are no longer valid,
+                // repaint everything on the next refresh.
+                check!(self.window.clearok(true));
+            }
             Action::EnableMouseCapture => {
                 self.buffer
                     .write_all(constants::ENABLE_MOUSE_CAPTURE.as_bytes())?;
@@ -274,6 +401,105 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                     .write_all(constants::DISABLE_MOUSE_CAPTURE.as_bytes())?;
                 self.buffer.flush()?;
             }
+            Action::EnableBracketedPaste => {
+                self.buffer
+                    .write_all(ansi::ENABLE_BRACKETED_PASTE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::DisableBracketedPaste => {
+                self.buffer
+                    .write_all(ansi::DISABLE_BRACKETED_PASTE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::EnableFocusChange => {
+                self.buffer
+                    .write_all(ansi::ENABLE_FOCUS_CHANGE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::DisableFocusChange => {
+                self.buffer
+                    .write_all(ansi::DISABLE_FOCUS_CHANGE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::BeginSynchronizedUpdate => {
+                self.buffer
+                    .write_all(ansi::BEGIN_SYNCHRONIZED_UPDATE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::EndSynchronizedUpdate => {
+                // The screen content is only written when the window is refreshed.
+                self.window.refresh();
+                self.buffer
+                    .write_all(ansi::END_SYNCHRONIZED_UPDATE.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::PushKeyboardEnhancementFlags(flags) => {
+                self.buffer
+                    .write_all(ansi::push_keyboard_enhancement_flags(flags).as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::PopKeyboardEnhancementFlags => {
+                self.buffer
+                    .write_all(ansi::POP_KEYBOARD_ENHANCEMENT_FLAGS.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::EnableModifyOtherKeys => {
+                self.buffer
+                    .write_all(ansi::ENABLE_MODIFY_OTHER_KEYS.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::DisableModifyOtherKeys => {
+                self.buffer
+                    .write_all(ansi::DISABLE_MODIFY_OTHER_KEYS.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            // curses writes text through its own screen buffer, escape codes can't be
+            // interleaved with it. The link text is shown as plain text instead.
+            Action::BeginHyperlink { .. } | Action::EndHyperlink => {}
+            Action::SetClipboard { selection, content } => {
+                self.buffer
+                    .write_all(ansi::set_clipboard(selection, &content).as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::Notify { title, body } => {
+                self.buffer
+                    .write_all(ansi::notify(&title, &body).as_bytes())?;
+                self.buffer.flush()?;
+            }
+            Action::MarkPromptStart => self.write_at_cursor(ansi::MARK_PROMPT_START)?,
+            Action::MarkCommandStart => self.write_at_cursor(ansi::MARK_COMMAND_START)?,
+            Action::MarkOutputStart => self.write_at_cursor(ansi::MARK_OUTPUT_START)?,
+            Action::MarkCommandFinished(exit_code) => {
+                self.write_at_cursor(&ansi::mark_command_finished(exit_code))?
+            }
+            Action::ReportWorkingDirectory(path) => {
+                self.buffer
+                    .write_all(ansi::report_working_directory(&path)?.as_bytes())?;
+                self.buffer.flush()?;
+            }
+            // curses changes the palette itself if it can, which keeps `color_content` up to date.
+            Action::SetPaletteColor(index, Color::Rgb(r, g, b))
+                if crosscurses::can_change_color() =>
+            {
+                check!(crosscurses::init_color(
+                    i16::from(index),
+                    to_curses_intensity(r),
+                    to_curses_intensity(g),
+                    to_curses_intensity(b)
+                ));
+                check!(self.window.refresh());
+            }
+            Action::SetPaletteColor(index, color) => match ansi::set_palette_color(index, color) {
+                Some(sequence) => {
+                    self.buffer.write_all(sequence.as_bytes())?;
+                    self.buffer.flush()?;
+                }
+                None => return Err(error::ErrorKind::ActionNotSupported(String::from(action))),
+            },
+            Action::ResetPalette => {
+                self.buffer.write_all(ansi::RESET_PALETTE.as_bytes())?;
+                self.buffer.flush()?;
+            }
             Action::ResetColor => {
                 let style = crosscurses::COLOR_PAIR(0 as crosscurses::chtype);
                 check!(self.window.attron(style));
@@ -299,7 +525,12 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                     Attribute::Reset => Some(crosscurses::Attribute::Normal),
                     Attribute::Bold => Some(crosscurses::Attribute::Bold),
                     Attribute::Italic => Some(crosscurses::Attribute::Italic),
-                    Attribute::Underlined => Some(crosscurses::Attribute::Underline),
+                    // curses knows only one underline style.
+                    Attribute::Underlined
+                    | Attribute::DoubleUnderlined
+                    | Attribute::Undercurled
+                    | Attribute::Underdotted
+                    | Attribute::Underdashed => Some(crosscurses::Attribute::Underline),
                     Attribute::SlowBlink | Attribute::RapidBlink => {
                         Some(crosscurses::Attribute::Blink)
                     }
@@ -334,6 +565,7 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             }
             Action::EnterAlternateScreen
             | Action::LeaveAlternateScreen
+            | Action::SetUnderlineColor(_)
             | Action::ScrollUp(_)
             | Action::ScrollDown(_) => check!(3),
         };
@@ -372,8 +604,71 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
 
                 Ok(Retrieved::Event(None))
             }
+            Value::Clipboard(selection) => Ok(Retrieved::Clipboard(
+                self.query(&ansi::query_clipboard(selection), input::parse_clipboard)?,
+            )),
+            Value::ColorSupport => Ok(Retrieved::ColorSupport(
+                color_support::from_colors(COLORS()),
+            )),
+            Value::DefaultColors => {
+                let (foreground, background) =
+                    query::default_colors(|query, parse| self.query(query, parse))?;
+
+                Ok(Retrieved::DefaultColors {
+                    foreground,
+                    background,
+                })
+            }
+            Value::TerminalIdentity => {
+                let identity = query::terminal_identity(|query, parse| self.query(query, parse))?;
+
+                Ok(Retrieved::TerminalIdentity(identity))
+            }
+            Value::WindowPixelSize => Ok(Retrieved::WindowPixelSize(query::window_pixel_size(
+                window_pixel_size(),
+                |query, parse| self.query(query, parse),
+            )?)),
+            Value::CellPixelSize => {
+                let (rows, columns) = self.window.get_max_yx();
+                let cells = (columns > 0 && rows > 0).then_some((columns as u16, rows as u16));
+
+                Ok(Retrieved::CellPixelSize(query::cell_pixel_size(
+                    window_pixel_size(),
+                    cells,
+                    |query, parse| self.query(query, parse),
+                )?))
+            }
+            Value::ModeStatus(mode) => Ok(Retrieved::ModeStatus(query::mode_status(
+                mode,
+                |query, parse| self.query(query, parse),
+            )?)),
+            Value::PaletteColor(index) => {
+                let color = query::palette_color(index, |query, parse| self.query(query, parse))?;
+
+                // If the terminal doesn't answer, fall back to the color that curses knows of.
+                let color = color.or_else(|| {
+                    if i32::from(index) < COLORS() {
+                        let (r, g, b) = crosscurses::color_content(i16::from(index));
+                        Some(Color::Rgb(
+                            from_curses_intensity(r),
+                            from_curses_intensity(g),
+                            from_curses_intensity(b),
+                        ))
+                    } else {
+                        None
+                    }
+                });
+
+                Ok(Retrieved::PaletteColor(color))
+            }
+            Value::Environment => Ok(Retrieved::Environment(environment::detect())),
+            Value::InputMode => Ok(Retrieved::InputMode(input_mode::get()?)),
         }
     }
+
+    fn output_is_tty(&self) -> Option<bool> {
+        Some(self.output_is_tty)
+    }
 }
 
 impl<W: Write> Drop for BackendImpl<W> {
@@ -406,7 +701,6 @@ fn initialize_keymap() -> HashMap<i32, Event> {
     map
 }
 
-#[allow(clippy::eq_op)]
 fn fill_key_codes<F>(target: &mut HashMap<i32, Event>, f: F)
 where
     F: Fn(i32) -> Option<String>,
@@ -440,26 +734,17 @@ where
         };
 
         let event = match modifier {
-            "3" => Event::Key(KeyEvent {
-                code: key,
-                modifiers: KeyModifiers::ALT,
-            }),
-            "4" => Event::Key(KeyEvent {
-                code: key,
-                modifiers: KeyModifiers::ALT | KeyModifiers::SHIFT,
-            }),
-            "5" => Event::Key(KeyEvent {
-                code: key,
-                modifiers: KeyModifiers::CONTROL,
-            }),
-            "6" => Event::Key(KeyEvent {
-                code: key,
-                modifiers: (KeyModifiers::CONTROL | KeyModifiers::CONTROL),
-            }),
-            "7" => Event::Key(KeyEvent {
-                code: key,
-                modifiers: (KeyModifiers::CONTROL | KeyModifiers::ALT),
-            }),
+            "3" => Event::Key(KeyEvent::new(key, KeyModifiers::ALT)),
+            "4" => Event::Key(KeyEvent::new(key, KeyModifiers::ALT | KeyModifiers::SHIFT)),
+            "5" => Event::Key(KeyEvent::new(key, KeyModifiers::CONTROL)),
+            "6" => Event::Key(KeyEvent::new(
+                key,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            )),
+            "7" => Event::Key(KeyEvent::new(
+                key,
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            )),
             _ => continue,
         };
 
@@ -469,7 +754,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::error;
+    use std::collections::HashMap;
+
+    use super::fill_key_codes;
+    use crate::{error, Event, KeyCode, KeyEvent, KeyModifiers};
 
     fn a(return_val: i32) -> error::Result<()> {
         check!(return_val);
@@ -483,4 +771,29 @@ mod test {
         assert!(a(3).is_err());
         assert!(a(-1).is_err());
     }
+
+    #[test]
+    fn test_fill_key_codes() {
+        let mut key_codes = HashMap::new();
+        fill_key_codes(&mut key_codes, |code| match code {
+            600 => Some(String::from("kUP6")),
+            601 => Some(String::from("kDC3")),
+            _ => None,
+        });
+
+        assert_eq!(
+            key_codes.get(&600),
+            Some(&Event::Key(KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )))
+        );
+        assert_eq!(
+            key_codes.get(&601),
+            Some(&Event::Key(KeyEvent::new(
+                KeyCode::Delete,
+                KeyModifiers::ALT
+            )))
+        );
+    }
 }
@@ -1,9 +1,19 @@
-use crate::{Color, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
+use crate::{
+    backend::{ansi, crosscurses::constants, input},
+    Color, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+};
 use crosscurses::{mmask_t, Input};
 use std::io::Write;
 
 impl<W: Write> super::BackendImpl<W> {
     pub fn parse_next(&self, input: crosscurses::Input) -> Event {
+        // crosscurses returns sequences it doesn't know character by character.
+        if input == Input::Character('\u{1b}') {
+            if let Some(event) = self.try_parse_escape_sequence() {
+                return event;
+            }
+        }
+
         // Try to map the crosscurses input event to an `KeyEvent` with possible modifiers.
         let key_event = self.try_parse_key(input).map_or(
             self.try_map_shift_key(input).map_or(
@@ -27,6 +37,58 @@ impl<W: Write> super::BackendImpl<W> {
         }
     }
 
+    /// Reads the CSI sequence following an ESC character, returns `None` if it is a single ESC key.
+    fn try_parse_escape_sequence(&self) -> Option<Event> {
+        // The remainder of the sequence is already available, don't wait for it.
+        self.window.timeout(0);
+
+        match self.window.getch() {
+            Some(Input::Character('[')) => {}
+            Some(input) => {
+                self.window.ungetch(&input);
+                return None;
+            }
+            None => return None,
+        }
+
+        let mut sequence = String::from("\x1B[");
+
+        while let Some(Input::Character(c)) = self.window.getch() {
+            sequence.push(c);
+
+            // The final byte of a CSI sequence is in the range 64-126.
+            if ('\x40'..='\x7E').contains(&c) {
+                break;
+            }
+        }
+
+        if sequence == ansi::BRACKETED_PASTE_START {
+            Some(self.read_paste())
+        } else {
+            Some(input::parse_csi(sequence.as_bytes()).unwrap_or(Event::Unknown))
+        }
+    }
+
+    /// Reads pasted text until the end of the bracketed paste.
+    fn read_paste(&self) -> Event {
+        self.window.timeout(constants::PASTE_TIMEOUT);
+
+        let mut text = String::new();
+
+        while let Some(input) = self.window.getch() {
+            if let Input::Character(c) = input {
+                text.push(c);
+
+                if text.ends_with(ansi::BRACKETED_PASTE_END) {
+                    text.truncate(text.len() - ansi::BRACKETED_PASTE_END.len());
+                    break;
+                }
+            }
+        }
+
+        Event::Paste(text)
+    }
+
     /// Matches on keys without modifiers, returns `None` if the key has modifiers or is not supported.
     pub fn try_parse_key(&self, input: crosscurses::Input) -> Option<KeyEvent> {
         let empty = KeyModifiers::empty();
@@ -52,118 +114,34 @@ impl<W: Write> super::BackendImpl<W> {
                 '\u{1b}' => Some(KeyCode::Esc.into()),
                 c => Some(KeyCode::Char(c).into()),
             },
-            Input::KeyDown => Some(KeyEvent {
-                code: KeyCode::Down,
-                modifiers: empty,
-            }),
-            Input::KeyUp => Some(KeyEvent {
-                code: KeyCode::Up,
-                modifiers: empty,
-            }),
-            Input::KeyLeft => Some(KeyEvent {
-                code: KeyCode::Left,
-                modifiers: empty,
-            }),
-            Input::KeyRight => Some(KeyEvent {
-                code: KeyCode::Right,
-                modifiers: empty,
-            }),
-            Input::KeyHome => Some(KeyEvent {
-                code: KeyCode::Home,
-                modifiers: empty,
-            }),
-            Input::KeyBackspace => Some(KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: empty,
-            }),
-            Input::KeyF0 => Some(KeyEvent {
-                code: KeyCode::F(0),
-                modifiers: empty,
-            }),
-            Input::KeyF1 => Some(KeyEvent {
-                code: KeyCode::F(1),
-                modifiers: empty,
-            }),
-            Input::KeyF2 => Some(KeyEvent {
-                code: KeyCode::F(2),
-                modifiers: empty,
-            }),
-            Input::KeyF3 => Some(KeyEvent {
-                code: KeyCode::F(3),
-                modifiers: empty,
-            }),
-            Input::KeyF4 => Some(KeyEvent {
-                code: KeyCode::F(4),
-                modifiers: empty,
-            }),
-            Input::KeyF5 => Some(KeyEvent {
-                code: KeyCode::F(5),
-                modifiers: empty,
-            }),
-            Input::KeyF6 => Some(KeyEvent {
-                code: KeyCode::F(6),
-                modifiers: empty,
-            }),
-            Input::KeyF7 => Some(KeyEvent {
-                code: KeyCode::F(7),
-                modifiers: empty,
-            }),
-            Input::KeyF8 => Some(KeyEvent {
-                code: KeyCode::F(8),
-                modifiers: empty,
-            }),
-            Input::KeyF9 => Some(KeyEvent {
-                code: KeyCode::F(9),
-                modifiers: empty,
-            }),
-            Input::KeyF10 => Some(KeyEvent {
-                code: KeyCode::F(10),
-                modifiers: empty,
-            }),
-            Input::KeyF11 => Some(KeyEvent {
-                code: KeyCode::F(11),
-                modifiers: empty,
-            }),
-            Input::KeyF12 => Some(KeyEvent {
-                code: KeyCode::F(12),
-                modifiers: empty,
-            }),
-            Input::KeyF13 => Some(KeyEvent {
-                code: KeyCode::F(13),
-                modifiers: empty,
-            }),
-            Input::KeyF14 => Some(KeyEvent {
-                code: KeyCode::F(14),
-                modifiers: empty,
-            }),
-            Input::KeyF15 => Some(KeyEvent {
-                code: KeyCode::F(15),
-                modifiers: empty,
-            }),
-            Input::KeyDL => Some(KeyEvent {
-                code: KeyCode::Delete,
-                modifiers: empty,
-            }),
-            Input::KeyIC => Some(KeyEvent {
-                code: KeyCode::Insert,
-                modifiers: empty,
-            }),
-            Input::KeyNPage => Some(KeyEvent {
-                code: KeyCode::PageDown,
-                modifiers: empty,
-            }),
-            Input::KeyPPage => Some(KeyEvent {
-                code: KeyCode::PageUp,
-                modifiers: empty,
-            }),
-            Input::KeyEnter => Some(KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: empty,
-            }),
-            Input::KeyEnd => Some(KeyEvent {
-                code: KeyCode::End,
-                modifiers: empty,
-            }),
+            Input::KeyDown => Some(KeyEvent::new(KeyCode::Down, empty)),
+            Input::KeyUp => Some(KeyEvent::new(KeyCode::Up, empty)),
+            Input::KeyLeft => Some(KeyEvent::new(KeyCode::Left, empty)),
+            Input::KeyRight => Some(KeyEvent::new(KeyCode::Right, empty)),
+            Input::KeyHome => Some(KeyEvent::new(KeyCode::Home, empty)),
+            Input::KeyBackspace => Some(KeyEvent::new(KeyCode::Backspace, empty)),
+            Input::KeyF0 => Some(KeyEvent::new(KeyCode::F(0), empty)),
+            Input::KeyF1 => Some(KeyEvent::new(KeyCode::F(1), empty)),
+            Input::KeyF2 => Some(KeyEvent::new(KeyCode::F(2), empty)),
+            Input::KeyF3 => Some(KeyEvent::new(KeyCode::F(3), empty)),
+            Input::KeyF4 => Some(KeyEvent::new(KeyCode::F(4), empty)),
+            Input::KeyF5 => Some(KeyEvent::new(KeyCode::F(5), empty)),
+            Input::KeyF6 => Some(KeyEvent::new(KeyCode::F(6), empty)),
+            Input::KeyF7 => Some(KeyEvent::new(KeyCode::F(7), empty)),
+            Input::KeyF8 => Some(KeyEvent::new(KeyCode::F(8), empty)),
+            Input::KeyF9 => Some(KeyEvent::new(KeyCode::F(9), empty)),
+            Input::KeyF10 => Some(KeyEvent::new(KeyCode::F(10), empty)),
+            Input::KeyF11 => Some(KeyEvent::new(KeyCode::F(11), empty)),
+            Input::KeyF12 => Some(KeyEvent::new(KeyCode::F(12), empty)),
+            Input::KeyF13 => Some(KeyEvent::new(KeyCode::F(13), empty)),
+            Input::KeyF14 => Some(KeyEvent::new(KeyCode::F(14), empty)),
+            Input::KeyF15 => Some(KeyEvent::new(KeyCode::F(15), empty)),
+            Input::KeyDL => Some(KeyEvent::new(KeyCode::Delete, empty)),
+            Input::KeyIC => Some(KeyEvent::new(KeyCode::Insert, empty)),
+            Input::KeyNPage => Some(KeyEvent::new(KeyCode::PageDown, empty)),
+            Input::KeyPPage => Some(KeyEvent::new(KeyCode::PageUp, empty)),
+            Input::KeyEnter => Some(KeyEvent::new(KeyCode::Enter, empty)),
+            Input::KeyEnd => Some(KeyEvent::new(KeyCode::End, empty)),
             _ => None,
         };
 
@@ -221,7 +199,9 @@ impl<W: Write> super::BackendImpl<W> {
                 // window is resized.
                 crosscurses::resize_term(0, 0);
 
-                Some(Event::Resize)
+                // Coordinates are reversed here
+                let (rows, columns) = self.window.get_max_yx();
+                Some(Event::Resize(columns as u16, rows as u16))
             }
             Input::KeyMouse => Some(self.map_mouse_event()),
             Input::Unknown(code) => {
@@ -1,4 +1,11 @@
-use std::{io, io::Write};
+use std::{
+    collections::VecDeque,
+    io,
+    io::Write,
+    sync::RwLock,
+    thread,
+    time::{Duration, Instant},
+};
 
 use crossterm::{
     cursor, event, style, terminal,
@@ -6,7 +13,13 @@ use crossterm::{
     ExecutableCommand, QueueableCommand,
 };
 
-use crate::{backend::Backend, error, error::ErrorKind, Action, Event, Retrieved, Value};
+use crate::backend::query;
+use crate::{
+    backend::{ansi, color_support, environment, input, input_mode, Backend},
+    error,
+    error::ErrorKind,
+    Action, Event, KeyEventKind, Retrieved, Value,
+};
 
 pub struct BackendImpl<W: Write> {
     // The internal buffer on which operations are performed and written to.
@@ -15,6 +28,50 @@ pub struct BackendImpl<W: Write> {
     // We need to check in the `drop` if we enabled it to prevent this.
     // Should be fixed in later crossterm releases.
     mouse_capture_enabled: bool,
+    // The number of pushed keyboard enhancement flags, crossterm reports key releases
+    // on Windows regardless, which are only passed on if enhancements were requested.
+    keyboard_enhancements: usize,
+    // Events that were read while waiting for the response to a query.
+    pending_events: RwLock<VecDeque<Event>>,
+}
+
+impl<W: Write> BackendImpl<W> {
+    /// Queries the terminal, returns `None` if it doesn't answer in time.
+    fn query<T>(
+        &self,
+        query: &str,
+        parse: impl FnMut(&[u8]) -> Option<T>,
+    ) -> error::Result<Option<T>> {
+        query::query(query, parse, |event| {
+            self.pending_events.write().unwrap().push_back(event)
+        })
+    }
+
+    /// Reads the next event, skips key repeats and releases unless keyboard enhancements are enabled.
+    fn read_event(&self, duration: Option<Duration>) -> error::Result<Option<Event>> {
+        let deadline = duration.map(|duration| Instant::now() + duration);
+
+        loop {
+            if let Some(deadline) = deadline {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+
+                if !event::poll(timeout)? {
+                    return Ok(None);
+                }
+            }
+
+            let event = Event::from(event::read()?);
+
+            match event {
+                Event::Key(key)
+                    if key.kind != KeyEventKind::Press && self.keyboard_enhancements == 0 =>
+                {
+                    continue
+                }
+                event => return Ok(Some(event)),
+            }
+        }
+    }
 }
 
 impl<W: Write> Backend<W> for BackendImpl<W> {
@@ -22,6 +79,8 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
         BackendImpl {
             buffer,
             mouse_capture_enabled: false,
+            keyboard_enhancements: 0,
+            pending_events: RwLock::new(VecDeque::new()),
         }
     }
 
@@ -46,6 +105,20 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             Action::SetTerminalSize(column, row) => buffer.queue(terminal::SetSize(column, row))?,
             Action::ScrollUp(rows) => buffer.queue(terminal::ScrollUp(rows))?,
             Action::ScrollDown(rows) => buffer.queue(terminal::ScrollDown(rows))?,
+            Action::DisableLineWrap => buffer.queue(terminal::DisableLineWrap)?,
+            Action::EnableLineWrap => buffer.queue(terminal::EnableLineWrap)?,
+            Action::Bell => {
+                buffer.write_all(ansi::BELL.as_bytes())?;
+                buffer
+            }
+            Action::Flash => {
+                buffer.write_all(ansi::ENABLE_REVERSE_SCREEN.as_bytes())?;
+                buffer.flush()?;
+                thread::sleep(ansi::FLASH_DURATION);
+                buffer.write_all(ansi::DISABLE_REVERSE_SCREEN.as_bytes())?;
+                buffer.flush()?;
+                buffer
+            }
             Action::EnterAlternateScreen => {
                 buffer.queue(terminal::EnterAlternateScreen)?;
                 buffer
@@ -60,6 +133,9 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             Action::SetBackgroundColor(color) => {
                 buffer.queue(style::SetBackgroundColor(style::Color::from(color)))?
             }
+            Action::SetUnderlineColor(color) => {
+                buffer.queue(style::SetUnderlineColor(style::Color::from(color)))?
+            }
             Action::SetAttribute(attr) => {
                 buffer.queue(style::SetAttribute(style::Attribute::from(attr)))?
             }
@@ -72,6 +148,32 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 disable_raw_mode()?;
                 return Ok(());
             }
+            #[cfg(unix)]
+            Action::SetInputMode(mode) => {
+                input_mode::set(mode)?;
+                return Ok(());
+            }
+            // The input mode is changed with termios, which Windows doesn't have.
+            #[cfg(not(unix))]
+            Action::SetInputMode(_) => {
+                return Err(ErrorKind::ActionNotSupported(String::from(action)))
+            }
+            Action::ResetTerminal | Action::SoftReset => {
+                // Not every terminal disables mouse event capturing on a soft reset.
+                if self.mouse_capture_enabled {
+                    self.mouse_capture_enabled = false;
+                    buffer.queue(event::DisableMouseCapture)?;
+                }
+                disable_raw_mode()?;
+                self.keyboard_enhancements = 0;
+
+                let sequence = match action {
+                    Action::ResetTerminal => ansi::RESET_TERMINAL,
+                    _ => ansi::SOFT_RESET,
+                };
+                buffer.write_all(sequence.as_bytes())?;
+                buffer
+            }
             Action::EnableMouseCapture => {
                 self.mouse_capture_enabled = true;
                 buffer.queue(event::EnableMouseCapture)?
@@ -80,6 +182,76 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 self.mouse_capture_enabled = false;
                 buffer.queue(event::DisableMouseCapture)?
             }
+            Action::EnableBracketedPaste => buffer.queue(event::EnableBracketedPaste)?,
+            Action::DisableBracketedPaste => buffer.queue(event::DisableBracketedPaste)?,
+            Action::EnableFocusChange => buffer.queue(event::EnableFocusChange)?,
+            Action::DisableFocusChange => buffer.queue(event::DisableFocusChange)?,
+            Action::BeginSynchronizedUpdate => buffer.queue(terminal::BeginSynchronizedUpdate)?,
+            Action::EndSynchronizedUpdate => buffer.queue(terminal::EndSynchronizedUpdate)?,
+            Action::PushKeyboardEnhancementFlags(flags) => {
+                // Not supported by the legacy Windows console, the flags only count once they're pushed.
+                buffer.queue(event::PushKeyboardEnhancementFlags(
+                    event::KeyboardEnhancementFlags::from(flags),
+                ))?;
+                self.keyboard_enhancements += 1;
+                buffer
+            }
+            Action::PopKeyboardEnhancementFlags => {
+                buffer.queue(event::PopKeyboardEnhancementFlags)?;
+                self.keyboard_enhancements = self.keyboard_enhancements.saturating_sub(1);
+                buffer
+            }
+            Action::BeginHyperlink { uri, id } => {
+                buffer.write_all(ansi::begin_hyperlink(&uri, id.as_deref()).as_bytes())?;
+                buffer
+            }
+            Action::EndHyperlink => {
+                buffer.write_all(ansi::END_HYPERLINK.as_bytes())?;
+                buffer
+            }
+            Action::SetClipboard { selection, content } => {
+                buffer.write_all(ansi::set_clipboard(selection, &content).as_bytes())?;
+                buffer
+            }
+            Action::Notify { title, body } => {
+                buffer.write_all(ansi::notify(&title, &body).as_bytes())?;
+                buffer
+            }
+            Action::MarkPromptStart => {
+                buffer.write_all(ansi::MARK_PROMPT_START.as_bytes())?;
+                buffer
+            }
+            Action::MarkCommandStart => {
+                buffer.write_all(ansi::MARK_COMMAND_START.as_bytes())?;
+                buffer
+            }
+            Action::MarkOutputStart => {
+                buffer.write_all(ansi::MARK_OUTPUT_START.as_bytes())?;
+                buffer
+            }
+            Action::MarkCommandFinished(exit_code) => {
+                buffer.write_all(ansi::mark_command_finished(exit_code).as_bytes())?;
+                buffer
+            }
+            Action::ReportWorkingDirectory(path) => {
+                buffer.write_all(ansi::report_working_directory(&path)?.as_bytes())?;
+                buffer
+            }
+            Action::SetPaletteColor(index, color) => match ansi::set_palette_color(index, color) {
+                Some(sequence) => {
+                    buffer.write_all(sequence.as_bytes())?;
+                    buffer
+                }
+                None => return Err(ErrorKind::ActionNotSupported(String::from(action))),
+            },
+            Action::ResetPalette => {
+                buffer.write_all(ansi::RESET_PALETTE.as_bytes())?;
+                buffer
+            }
+            // crossterm doesn't decode the modifyOtherKeys sequences, key presses would be lost.
+            Action::EnableModifyOtherKeys | Action::DisableModifyOtherKeys => {
+                return Err(ErrorKind::ActionNotSupported(String::from(action)))
+            }
         };
 
         Ok(())
@@ -102,20 +274,65 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 Retrieved::CursorPosition(position.0, position.1)
             }
             Value::Event(duration) => {
-                if let Some(duration) = duration {
-                    if event::poll(duration)? {
-                        let event = event::read()?;
-                        Retrieved::Event(Some(Event::from(event)))
-                    } else {
-                        Retrieved::Event(None)
-                    }
+                if let Some(event) = self.pending_events.write().unwrap().pop_front() {
+                    Retrieved::Event(Some(event))
                 } else {
-                    let event = event::read()?;
-                    Retrieved::Event(Some(Event::from(event)))
+                    Retrieved::Event(self.read_event(duration)?)
                 }
             }
+            Value::Clipboard(selection) => Retrieved::Clipboard(
+                self.query(&ansi::query_clipboard(selection), input::parse_clipboard)?,
+            ),
+            Value::ColorSupport => Retrieved::ColorSupport(color_support::detect()),
+            Value::DefaultColors => {
+                let (foreground, background) =
+                    query::default_colors(|query, parse| self.query(query, parse))?;
+
+                Retrieved::DefaultColors {
+                    foreground,
+                    background,
+                }
+            }
+            Value::TerminalIdentity => {
+                let identity = query::terminal_identity(|query, parse| self.query(query, parse))?;
+
+                Retrieved::TerminalIdentity(identity)
+            }
+            Value::WindowPixelSize => {
+                let size = terminal::window_size().ok();
+
+                Retrieved::WindowPixelSize(query::window_pixel_size(
+                    size.map(|size| (size.width, size.height)),
+                    |query, parse| self.query(query, parse),
+                )?)
+            }
+            Value::CellPixelSize => {
+                let size = terminal::window_size().ok();
+
+                Retrieved::CellPixelSize(query::cell_pixel_size(
+                    size.as_ref().map(|size| (size.width, size.height)),
+                    size.as_ref().map(|size| (size.columns, size.rows)),
+                    |query, parse| self.query(query, parse),
+                )?)
+            }
+            Value::ModeStatus(mode) => {
+                Retrieved::ModeStatus(query::mode_status(mode, |query, parse| {
+                    self.query(query, parse)
+                })?)
+            }
+            Value::PaletteColor(index) => {
+                Retrieved::PaletteColor(query::palette_color(index, |query, parse| {
+                    self.query(query, parse)
+                })?)
+            }
+            Value::Environment => Retrieved::Environment(environment::detect()),
+            Value::InputMode => Retrieved::InputMode(input_mode::get()?),
         })
     }
+
+    fn output_is_tty(&self) -> Option<bool> {
+        None
+    }
 }
 
 impl<W: Write> Drop for BackendImpl<W> {
@@ -1,10 +1,10 @@
-use std::{error::Error, io};
+use std::convert::TryFrom;
 
 use crossterm::{event, style, terminal};
 
 use crate::{
-    error::ErrorKind, Attribute, Clear, Color, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton,
-    MouseEvent,
+    Attribute, Clear, Color, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    KeyboardEnhancementFlags, MouseButton, MouseEvent,
 };
 
 impl From<Attribute> for style::Attribute {
@@ -25,12 +25,16 @@ impl From<Attribute> for style::Attribute {
             Attribute::NormalIntensity => style::Attribute::NormalIntensity,
             Attribute::ItalicOff => style::Attribute::NoItalic,
             Attribute::UnderlinedOff => style::Attribute::NoUnderline,
+            Attribute::DoubleUnderlined => style::Attribute::DoubleUnderlined,
+            Attribute::Undercurled => style::Attribute::Undercurled,
+            Attribute::Underdotted => style::Attribute::Underdotted,
+            Attribute::Underdashed => style::Attribute::Underdashed,
             Attribute::BlinkOff => style::Attribute::NoBlink,
             Attribute::ReversedOff => style::Attribute::NoReverse,
             Attribute::ConcealOff => style::Attribute::NoHidden,
             Attribute::CrossedOff => style::Attribute::NotCrossedOut,
             Attribute::Framed => style::Attribute::Framed,
-            Attribute::__Nonexhaustive => style::Attribute::__Nonexhaustive,
+            Attribute::__Nonexhaustive => unreachable!("`__Nonexhaustive` is not an attribute."),
         }
     }
 }
@@ -83,25 +87,23 @@ impl From<event::MouseButton> for MouseButton {
     }
 }
 
-impl From<event::MouseEvent> for MouseEvent {
-    fn from(event: event::MouseEvent) -> Self {
-        match event {
-            event::MouseEvent::Down(btn, x, y, modifiers) => {
-                MouseEvent::Down(btn.into(), x, y, modifiers.into())
-            }
-            event::MouseEvent::Up(btn, x, y, modifiers) => {
-                MouseEvent::Up(btn.into(), x, y, modifiers.into())
-            }
-            event::MouseEvent::Drag(btn, x, y, modifiers) => {
-                MouseEvent::Drag(btn.into(), x, y, modifiers.into())
-            }
-            event::MouseEvent::ScrollDown(x, y, modifiers) => {
-                MouseEvent::ScrollUp(x, y, modifiers.into())
-            }
-            event::MouseEvent::ScrollUp(x, y, modifiers) => {
-                MouseEvent::ScrollDown(x, y, modifiers.into())
-            }
-        }
+impl TryFrom<event::MouseEvent> for MouseEvent {
+    type Error = ();
+
+    fn try_from(event: event::MouseEvent) -> Result<Self, Self::Error> {
+        let (x, y) = (event.column, event.row);
+        let modifiers = KeyModifiers::from(event.modifiers);
+
+        Ok(match event.kind {
+            event::MouseEventKind::Down(btn) => MouseEvent::Down(btn.into(), x, y, modifiers),
+            event::MouseEventKind::Up(btn) => MouseEvent::Up(btn.into(), x, y, modifiers),
+            event::MouseEventKind::Drag(btn) => MouseEvent::Drag(btn.into(), x, y, modifiers),
+            event::MouseEventKind::ScrollDown => MouseEvent::ScrollDown(x, y, modifiers),
+            event::MouseEventKind::ScrollUp => MouseEvent::ScrollUp(x, y, modifiers),
+            event::MouseEventKind::Moved
+            | event::MouseEventKind::ScrollLeft
+            | event::MouseEventKind::ScrollRight => return Err(()),
+        })
     }
 }
 
@@ -110,6 +112,9 @@ impl From<event::KeyModifiers> for KeyModifiers {
         let shift = modifiers.contains(event::KeyModifiers::SHIFT);
         let ctrl = modifiers.contains(event::KeyModifiers::CONTROL);
         let alt = modifiers.contains(event::KeyModifiers::ALT);
+        let super_ = modifiers.contains(event::KeyModifiers::SUPER);
+        let hyper = modifiers.contains(event::KeyModifiers::HYPER);
+        let meta = modifiers.contains(event::KeyModifiers::META);
 
         let mut modifiers = KeyModifiers::empty();
 
@@ -122,14 +127,71 @@ impl From<event::KeyModifiers> for KeyModifiers {
         if alt {
             modifiers |= KeyModifiers::ALT;
         }
+        if super_ {
+            modifiers |= KeyModifiers::SUPER;
+        }
+        if hyper {
+            modifiers |= KeyModifiers::HYPER;
+        }
+        if meta {
+            modifiers |= KeyModifiers::META;
+        }
 
         modifiers
     }
 }
 
-impl From<event::KeyCode> for KeyCode {
-    fn from(code: event::KeyCode) -> Self {
-        match code {
+impl From<KeyboardEnhancementFlags> for event::KeyboardEnhancementFlags {
+    fn from(flags: KeyboardEnhancementFlags) -> Self {
+        let mut enhancement_flags = event::KeyboardEnhancementFlags::empty();
+
+        if flags.contains(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES) {
+            enhancement_flags |= event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+        }
+        if flags.contains(KeyboardEnhancementFlags::REPORT_EVENT_TYPES) {
+            enhancement_flags |= event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        }
+        if flags.contains(KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS) {
+            enhancement_flags |= event::KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS;
+        }
+        if flags.contains(KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES) {
+            enhancement_flags |= event::KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES;
+        }
+
+        enhancement_flags
+    }
+}
+
+impl From<event::KeyEventState> for KeyModifiers {
+    fn from(state: event::KeyEventState) -> Self {
+        let mut modifiers = KeyModifiers::empty();
+
+        if state.contains(event::KeyEventState::CAPS_LOCK) {
+            modifiers |= KeyModifiers::CAPS_LOCK;
+        }
+        if state.contains(event::KeyEventState::NUM_LOCK) {
+            modifiers |= KeyModifiers::NUM_LOCK;
+        }
+
+        modifiers
+    }
+}
+
+impl From<event::KeyEventKind> for KeyEventKind {
+    fn from(kind: event::KeyEventKind) -> Self {
+        match kind {
+            event::KeyEventKind::Press => KeyEventKind::Press,
+            event::KeyEventKind::Repeat => KeyEventKind::Repeat,
+            event::KeyEventKind::Release => KeyEventKind::Release,
+        }
+    }
+}
+
+impl TryFrom<event::KeyCode> for KeyCode {
+    type Error = ();
+
+    fn try_from(code: event::KeyCode) -> Result<Self, Self::Error> {
+        Ok(match code {
             event::KeyCode::Backspace => KeyCode::Backspace,
             event::KeyCode::Enter => KeyCode::Enter,
             event::KeyCode::Left => KeyCode::Left,
@@ -148,34 +210,36 @@ impl From<event::KeyCode> for KeyCode {
             event::KeyCode::Char(c) => KeyCode::Char(c),
             event::KeyCode::Null => KeyCode::Null,
             event::KeyCode::Esc => KeyCode::Esc,
-        }
+            // Lock, media, and modifier keys are only reported with enhanced keyboard reporting.
+            _ => return Err(()),
+        })
     }
 }
 
-impl From<event::KeyEvent> for KeyEvent {
-    fn from(event: event::KeyEvent) -> Self {
-        KeyEvent {
-            code: KeyCode::from(event.code),
-            modifiers: KeyModifiers::from(event.modifiers),
-        }
+impl TryFrom<event::KeyEvent> for KeyEvent {
+    type Error = ();
+
+    fn try_from(event: event::KeyEvent) -> Result<Self, Self::Error> {
+        Ok(KeyEvent {
+            code: KeyCode::try_from(event.code)?,
+            // crossterm reports the lock states separately from the modifiers.
+            modifiers: KeyModifiers::from(event.modifiers) | KeyModifiers::from(event.state),
+            kind: KeyEventKind::from(event.kind),
+        })
     }
 }
 
 impl From<event::Event> for Event {
     fn from(event: event::Event) -> Self {
         match event {
-            event::Event::Key(key) => Event::Key(KeyEvent::from(key)),
-            event::Event::Mouse(mouse) => Event::Mouse(MouseEvent::from(mouse)),
-            event::Event::Resize(_x, _y) => Event::Resize,
-        }
-    }
-}
-
-impl From<crossterm::ErrorKind> for ErrorKind {
-    fn from(error: crossterm::ErrorKind) -> Self {
-        match error {
-            crossterm::ErrorKind::IoError(e) => ErrorKind::IoError(e),
-            e => ErrorKind::IoError(io::Error::new(io::ErrorKind::Other, e.description())),
+            event::Event::Key(key) => KeyEvent::try_from(key).map_or(Event::Unknown, Event::Key),
+            event::Event::Mouse(mouse) => {
+                MouseEvent::try_from(mouse).map_or(Event::Unknown, Event::Mouse)
+            }
+            event::Event::Paste(text) => Event::Paste(text),
+            event::Event::Resize(columns, rows) => Event::Resize(columns, rows),
+            event::Event::FocusGained => Event::FocusGained,
+            event::Event::FocusLost => Event::FocusLost,
         }
     }
 }
@@ -0,0 +1,110 @@
+//! Detection of the environment that the application runs in, from environment variables.
+
+use std::env;
+
+use crate::{Environment, Multiplexer};
+
+/// Variables that continuous integration services set.
+const CI_VARIABLES: &[&str] = &[
+    "CI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TRAVIS",
+    "TF_BUILD",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+];
+
+/// Detects the environment from the variables of this process.
+pub(crate) fn detect() -> Environment {
+    from_variables(|name| env::var(name).ok())
+}
+
+fn from_variables(var: impl Fn(&str) -> Option<String>) -> Environment {
+    // Variables that are set to an empty string count as not set.
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+    let term = var("TERM");
+
+    let multiplexer = if var("TMUX").is_some() {
+        Some(Multiplexer::Tmux)
+    } else if var("STY").is_some() {
+        Some(Multiplexer::Screen)
+    } else if var("ZELLIJ").is_some() {
+        Some(Multiplexer::Zellij)
+    } else {
+        // The variables are not passed on over SSH, `TERM` is.
+        match term.as_deref() {
+            Some(term) if term.starts_with("tmux") => Some(Multiplexer::Tmux),
+            Some(term) if term.starts_with("screen") => Some(Multiplexer::Screen),
+            _ => None,
+        }
+    };
+
+    Environment {
+        dumb: term.as_deref() == Some("dumb"),
+        term,
+        term_program: var("TERM_PROGRAM"),
+        multiplexer,
+        ssh: ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+            .iter()
+            .any(|name| var(name).is_some()),
+        ci: CI_VARIABLES
+            .iter()
+            .any(|name| var(name).is_some_and(|value| value != "false")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::from_variables;
+    use crate::{Environment, Multiplexer};
+
+    fn environment(variables: &[(&str, &str)]) -> Environment {
+        from_variables(|name| {
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_environment() {
+        assert_eq!(environment(&[]), Environment::default());
+
+        assert_eq!(
+            environment(&[
+                ("TERM", "tmux-256color"),
+                ("TERM_PROGRAM", "tmux"),
+                ("TMUX", "/tmp/tmux-1000/default,1234,0"),
+                ("SSH_CONNECTION", "10.0.0.1 52000 10.0.0.2 22"),
+            ]),
+            Environment {
+                term: Some("tmux-256color".to_string()),
+                term_program: Some("tmux".to_string()),
+                multiplexer: Some(Multiplexer::Tmux),
+                ssh: true,
+                ci: false,
+                dumb: false,
+            }
+        );
+
+        assert_eq!(
+            environment(&[("TERM", "screen.xterm-256color")]).multiplexer,
+            Some(Multiplexer::Screen)
+        );
+        assert_eq!(
+            environment(&[("ZELLIJ", "0")]).multiplexer,
+            Some(Multiplexer::Zellij)
+        );
+
+        let ci = environment(&[("TERM", "dumb"), ("GITHUB_ACTIONS", "true")]);
+        assert!(ci.ci && ci.dumb);
+
+        assert!(!environment(&[("CI", "false")]).ci);
+        assert!(!environment(&[("SSH_TTY", "")]).ssh);
+    }
+}
@@ -0,0 +1,1097 @@
+//! Decoding of input sequences that not every backend library understands.
+
+use std::{collections::VecDeque, mem, str};
+
+use crate::{
+    backend::{ansi, base64},
+    Color, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, ModeStatus, MouseButton,
+    MouseEvent, TerminalIdentity,
+};
+
+/// The maximum length of an OSC or DCS response, large enough for the content of the clipboard.
+const MAX_STRING_LENGTH: usize = 1024 * 1024;
+
+/// Input that is read from the terminal.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Parsed {
+    /// An input event.
+    Event(Event),
+    /// The complete escape sequence of a response to a query.
+    Response(Vec<u8>),
+}
+
+/// Decodes the bytes read from the terminal into events and responses.
+///
+/// Bytes are buffered until an event or response is complete,
+/// decoded input can be taken with the `Iterator` implementation.
+#[derive(Default)]
+pub(crate) struct Parser {
+    // The bytes of the event that is currently read.
+    buffer: Vec<u8>,
+    // The pasted text, if a bracketed paste is currently read.
+    paste: Option<Vec<u8>>,
+    parsed: VecDeque<Parsed>,
+}
+
+impl Parser {
+    /// Decodes the given bytes.
+    ///
+    /// `more` indicates that more input is directly available after these bytes.
+    /// This is used to tell a single ESC key press apart from the start of an escape sequence.
+    pub(crate) fn advance(&mut self, bytes: &[u8], more: bool) {
+        for (index, byte) in bytes.iter().enumerate() {
+            self.advance_byte(*byte, more || index + 1 < bytes.len());
+        }
+    }
+
+    fn advance_byte(&mut self, byte: u8, more: bool) {
+        if let Some(mut text) = self.paste.take() {
+            text.push(byte);
+
+            if text.ends_with(ansi::BRACKETED_PASTE_END.as_bytes()) {
+                text.truncate(text.len() - ansi::BRACKETED_PASTE_END.len());
+                self.parsed.push_back(Parsed::Event(Event::Paste(
+                    String::from_utf8_lossy(&text).into_owned(),
+                )));
+            } else {
+                self.paste = Some(text);
+            }
+
+            return;
+        }
+
+        self.buffer.push(byte);
+
+        if self.buffer == ansi::BRACKETED_PASTE_START.as_bytes() {
+            self.buffer.clear();
+            self.paste = Some(Vec::new());
+        } else if self.buffer == b"\x1B\x1B" {
+            // The second ESC is a key press or starts an escape sequence itself.
+            self.split_off(key(KeyCode::Esc, KeyModifiers::empty()), 1, more);
+        } else if is_aborted_string(&self.buffer) {
+            // The bytes were typed after Alt + ] or Alt + P, decode them as keys again.
+            let event = key(KeyCode::Char(self.buffer[1] as char), KeyModifiers::ALT);
+            self.split_off(event, 2, more);
+        } else if let Some(parsed) = parse_input(&self.buffer, more) {
+            self.buffer.clear();
+            self.parsed.push_back(parsed);
+        }
+    }
+
+    /// Takes the event for the first `length` bytes of the buffer, the remaining bytes are decoded again.
+    fn split_off(&mut self, event: Event, length: usize, more: bool) {
+        let bytes = mem::take(&mut self.buffer);
+        self.parsed.push_back(Parsed::Event(event));
+
+        let rest = &bytes[length..];
+        for (index, byte) in rest.iter().enumerate() {
+            self.advance_byte(*byte, more || index + 1 < rest.len());
+        }
+    }
+}
+
+impl Iterator for Parser {
+    type Item = Parsed;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parsed.pop_front()
+    }
+}
+
+/// Parses the event or response at the start of the buffer.
+///
+/// Returns `None` if more bytes are needed to complete it.
+fn parse_input(buffer: &[u8], more: bool) -> Option<Parsed> {
+    if buffer[0] != b'\x1B' {
+        return parse_char(buffer).map(Parsed::Event);
+    }
+
+    let event = match buffer.get(1) {
+        None if more => return None,
+        None => key(KeyCode::Esc, KeyModifiers::empty()),
+        Some(b'[') => return parse_csi_buffer(buffer),
+        // OSC and DCS responses, unless it is a single Alt + ] or Alt + P key press.
+        Some(b']') | Some(b'P') if buffer.len() > 2 || more => {
+            return parse_string_buffer(buffer);
+        }
+        Some(b'O') => match buffer.get(2) {
+            None if more => return None,
+            None => key(KeyCode::Char('O'), KeyModifiers::ALT),
+            // F1-F4
+            Some(&byte @ b'P'..=b'S') => key(KeyCode::F(1 + byte - b'P'), KeyModifiers::empty()),
+            Some(_) => Event::Unknown,
+        },
+        Some(_) => match parse_char(&buffer[1..])? {
+            Event::Key(event) => key(event.code, event.modifiers | KeyModifiers::ALT),
+            event => event,
+        },
+    };
+
+    Some(Parsed::Event(event))
+}
+
+/// Parses a (possibly multi-byte) character, returns `None` if the character is not complete.
+fn parse_char(buffer: &[u8]) -> Option<Event> {
+    let c = match str::from_utf8(buffer) {
+        Ok(string) => string.chars().next()?,
+        // The character is not complete yet.
+        Err(e) if e.error_len().is_none() => return None,
+        Err(_) => return Some(Event::Unknown),
+    };
+
+    Some(match c {
+        '\r' | '\n' => key(KeyCode::Enter, KeyModifiers::empty()),
+        '\t' => key(KeyCode::Tab, KeyModifiers::empty()),
+        '\x7F' => key(KeyCode::Backspace, KeyModifiers::empty()),
+        '\0' => key(KeyCode::Null, KeyModifiers::empty()),
+        c @ '\x01'..='\x1A' => key(
+            KeyCode::Char((c as u8 - 0x1 + b'a') as char),
+            KeyModifiers::CONTROL,
+        ),
+        c @ '\x1C'..='\x1F' => key(
+            KeyCode::Char((c as u8 - 0x1C + b'4') as char),
+            KeyModifiers::CONTROL,
+        ),
+        c => key(KeyCode::Char(c), KeyModifiers::empty()),
+    })
+}
+
+/// Parses the CSI sequence in the buffer, returns `None` if the sequence is not complete.
+fn parse_csi_buffer(buffer: &[u8]) -> Option<Parsed> {
+    let complete = match buffer.get(2)? {
+        // X10 mouse encoding: ESC [ M Cb Cx Cy
+        b'M' => buffer.len() == 6,
+        // Linux console function keys: ESC [ [ A
+        b'[' => buffer.len() == 4,
+        // The final byte of a CSI sequence is in the range 64-126.
+        _ => (0x40..=0x7E).contains(buffer.last()?),
+    };
+
+    if !complete {
+        return None;
+    }
+
+    Some(match parse_csi(buffer) {
+        Some(event) => Parsed::Event(event),
+        None if is_csi_response(buffer) => Parsed::Response(buffer.to_vec()),
+        None => Parsed::Event(Event::Unknown),
+    })
+}
+
+/// Returns whether a complete CSI sequence, that is not an event, is a response to a query.
+fn is_csi_response(sequence: &[u8]) -> bool {
+    // Private parameters (e.g. `?` and `>`) are only used by responses.
+    let private = matches!(sequence.get(2), Some(b'<'..=b'?'));
+    // Cursor position, device status, device attributes, window and mode reports.
+    let report = matches!(sequence.last(), Some(b'R' | b'n' | b'c' | b't' | b'y'));
+
+    private || report
+}
+
+/// Parses the OSC or DCS string in the buffer, which ends with BEL or ST.
+///
+/// Returns `None` if the string is not complete.
+fn parse_string_buffer(buffer: &[u8]) -> Option<Parsed> {
+    if buffer.ends_with(b"\x07") || buffer.ends_with(b"\x1B\\") {
+        Some(Parsed::Response(buffer.to_vec()))
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the buffer started like an OSC or DCS response, but can't be one.
+///
+/// Only the responses to the queries are recognized (`ESC ] number ;` and `ESC P > |`),
+/// they can't contain control characters and are limited in length.
+fn is_aborted_string(buffer: &[u8]) -> bool {
+    let valid_prefix = match buffer {
+        [b'\x1B', b']', content @ ..] => {
+            let digits = content
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+
+            match content.get(digits) {
+                None => true,
+                Some(b';') => digits > 0,
+                Some(_) => false,
+            }
+        }
+        [b'\x1B', b'P', content @ ..] => {
+            let length = content.len().min(2);
+            content[..length] == b">|"[..length]
+        }
+        _ => return false,
+    };
+
+    let aborted = match buffer[2..] {
+        // A new escape sequence starts, the string terminator is `ESC \`.
+        [.., b'\x1B', byte] => byte != b'\\',
+        [.., byte] => byte != b'\x07' && byte != b'\x1B' && (byte < 0x20 || byte == 0x7F),
+        [] => false,
+    };
+
+    !valid_prefix || aborted || buffer.len() > MAX_STRING_LENGTH
+}
+
+/// Returns the content of an OSC response (`ESC ] content (BEL or ST)`) with the given prefix.
+///
+/// Returns `None` if the response is an other response.
+fn osc_content<'a>(response: &'a [u8], prefix: &str) -> Option<&'a str> {
+    string_content(response.strip_prefix(b"\x1B]")?, prefix)
+}
+
+/// Returns the content of a DCS response (`ESC P content ST`) with the given prefix.
+///
+/// Returns `None` if the response is an other response.
+fn dcs_content<'a>(response: &'a [u8], prefix: &str) -> Option<&'a str> {
+    string_content(response.strip_prefix(b"\x1BP")?, prefix)
+}
+
+fn string_content<'a>(content: &'a [u8], prefix: &str) -> Option<&'a str> {
+    let content = content
+        .strip_suffix(b"\x07")
+        .or_else(|| content.strip_suffix(b"\x1B\\"))?;
+
+    str::from_utf8(content).ok()?.strip_prefix(prefix)
+}
+
+/// Returns the parameters of a CSI response (`ESC [ prefix parameters suffix`),
+/// the suffix is the final byte and any intermediate bytes before it.
+///
+/// Returns `None` if the response is an other response.
+fn csi_parameters(response: &[u8], prefix: &str, suffix: &str) -> Option<Vec<u16>> {
+    let parameters = response
+        .strip_prefix(b"\x1B[")?
+        .strip_prefix(prefix.as_bytes())?
+        .strip_suffix(suffix.as_bytes())?;
+
+    str::from_utf8(parameters)
+        .ok()?
+        .split(';')
+        .map(|parameter| parameter.parse().ok())
+        .collect()
+}
+
+/// Parses the response to a clipboard query: `ESC ] 52 ; selection ; base64 ST`.
+pub(crate) fn parse_clipboard(response: &[u8]) -> Option<String> {
+    let content = osc_content(response, "52;")?;
+    let (_, data) = content.split_once(';')?;
+
+    String::from_utf8(base64::decode(data)?).ok()
+}
+
+/// Parses a color in the format of `XParseColor`: `rgb:R/G/B` with 1 to 4 hexadecimal digits
+/// per component. Some terminals add an alpha component (`rgba:R/G/B/A`), which is ignored.
+fn parse_color_spec(spec: &str) -> Option<Color> {
+    let components = spec
+        .strip_prefix("rgb:")
+        .or_else(|| spec.strip_prefix("rgba:"))?;
+
+    let mut components = components.split('/').map(|component| {
+        if component.is_empty() || component.len() > 4 {
+            return None;
+        }
+
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1 << (4 * component.len())) - 1;
+
+        Some((value * 255 / max) as u8)
+    });
+
+    let (r, g, b) = (
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    );
+    Some(Color::Rgb(r, g, b))
+}
+
+/// Parses the response to a query of the cursor position: `ESC [ row ; column R`.
+///
+/// Returns the 0-based (column, row).
+// The other backends ask their library for the cursor position.
+#[cfg_attr(not(feature = "termion-backend"), allow(dead_code))]
+pub(crate) fn parse_cursor_position(response: &[u8]) -> Option<(u16, u16)> {
+    match csi_parameters(response, "", "R")?.as_slice() {
+        [row, column] => Some((column.saturating_sub(1), row.saturating_sub(1))),
+        _ => None,
+    }
+}
+
+/// Parses the response to a query of the default foreground color: `ESC ] 10 ; rgb:R/G/B ST`.
+pub(crate) fn parse_foreground_color(response: &[u8]) -> Option<Color> {
+    parse_color_spec(osc_content(response, "10;")?)
+}
+
+/// Parses the response to a query of the default background color: `ESC ] 11 ; rgb:R/G/B ST`.
+pub(crate) fn parse_background_color(response: &[u8]) -> Option<Color> {
+    parse_color_spec(osc_content(response, "11;")?)
+}
+
+/// Adds a response to the terminal identity query to `identity`:
+/// XTVERSION (`ESC P > | name(version) ST`), DA2 (`ESC [ > type ; version ; ... c`)
+/// or DA1 (`ESC [ ? features c`).
+///
+/// Returns `true` for the response to DA1, which is the last one.
+pub(crate) fn parse_terminal_identity(identity: &mut TerminalIdentity, response: &[u8]) -> bool {
+    if let Some(text) = dcs_content(response, ">|") {
+        // Most terminals answer `name(version)`, some `name version`.
+        let (name, version) = match text.strip_suffix(')').and_then(|text| text.split_once('(')) {
+            Some((name, version)) => (name, Some(version)),
+            None => match text.split_once(' ') {
+                Some((name, version)) => (name, Some(version)),
+                None => (text, None),
+            },
+        };
+
+        identity.name = Some(name.trim().to_string());
+        identity.version = version.map(|version| version.trim().to_string());
+    } else if let Some(parameters) = csi_parameters(response, ">", "c") {
+        identity.device_type = parameters.first().copied();
+        identity.firmware_version = parameters.get(1).copied();
+    } else if let Some(parameters) = csi_parameters(response, "?", "c") {
+        identity.features = parameters;
+        return true;
+    }
+
+    false
+}
+
+/// Parses the response to a query of the window size in pixels: `ESC [ 4 ; height ; width t`.
+///
+/// Returns (width, height).
+pub(crate) fn parse_window_pixel_size(response: &[u8]) -> Option<(u16, u16)> {
+    parse_size_report(response, 4)
+}
+
+/// Parses the response to a query of the cell size in pixels: `ESC [ 6 ; height ; width t`.
+///
+/// Returns (width, height).
+pub(crate) fn parse_cell_pixel_size(response: &[u8]) -> Option<(u16, u16)> {
+    parse_size_report(response, 6)
+}
+
+fn parse_size_report(response: &[u8], kind: u16) -> Option<(u16, u16)> {
+    match csi_parameters(response, "", "t")?.as_slice() {
+        [report, height, width] if *report == kind => Some((*width, *height)),
+        _ => None,
+    }
+}
+
+/// Parses the response to a query of the given DEC private mode (DECRPM): `ESC [ ? mode ; status $ y`.
+pub(crate) fn parse_mode_status(response: &[u8], mode: u16) -> Option<ModeStatus> {
+    match csi_parameters(response, "?", "$y")?.as_slice() {
+        [number, status] if *number == mode => Some(match status {
+            1 => ModeStatus::Set,
+            2 => ModeStatus::Reset,
+            3 => ModeStatus::PermanentlySet,
+            4 => ModeStatus::PermanentlyReset,
+            _ => ModeStatus::NotRecognized,
+        }),
+        _ => None,
+    }
+}
+
+/// Parses the response to a query of a palette color: `ESC ] 4 ; index ; rgb:R/G/B ST`.
+pub(crate) fn parse_palette_color(response: &[u8], index: u8) -> Option<Color> {
+    parse_color_spec(osc_content(response, &format!("4;{};", index))?)
+}
+
+/// Parses a complete CSI sequence (`ESC [ ... final byte`) into an event.
+///
+/// Returns `None` if the sequence is not supported.
+pub(crate) fn parse_csi(sequence: &[u8]) -> Option<Event> {
+    let sequence = sequence.strip_prefix(b"\x1B[")?;
+
+    match sequence {
+        [b'M', cb, cx, cy] => {
+            // X10 mouse encoding, all values are offset by 32 and positions are 1-based.
+            let cb = u16::from(cb.checked_sub(32)?);
+            let (x, y) = (cx.saturating_sub(33), cy.saturating_sub(33));
+            return parse_mouse(cb, u16::from(x), u16::from(y), true);
+        }
+        [b'[', byte @ b'A'..=b'E'] => {
+            return Some(key(KeyCode::F(1 + byte - b'A'), KeyModifiers::empty()));
+        }
+        _ => {}
+    }
+
+    let (&final_byte, parameters) = sequence.split_last()?;
+    let parameters = str::from_utf8(parameters).ok()?;
+
+    if let Some(parameters) = parameters.strip_prefix('<') {
+        // SGR mouse encoding: ESC [ < Cb ; Cx ; Cy (M or m)
+        return match final_byte {
+            b'M' | b'm' => parse_sgr_mouse(parameters, final_byte == b'M'),
+            _ => None,
+        };
+    }
+
+    if parameters.starts_with(|c: char| !c.is_ascii_digit() && c != ';' && c != ':') {
+        // Private parameters (e.g. `?` and `>`) are used by responses, not by input events.
+        return None;
+    }
+
+    let parameters = split_parameters(parameters)?;
+
+    match final_byte {
+        b'I' if sequence.len() == 1 => Some(Event::FocusGained),
+        b'O' if sequence.len() == 1 => Some(Event::FocusLost),
+        b'M' => parse_rxvt_mouse(&parameters),
+        b'~' => parse_special_key(&parameters),
+        b'u' => parse_kitty_key(&parameters),
+        _ => parse_modified_key(&parameters, final_byte),
+    }
+}
+
+/// Splits CSI parameters (`1;5:3`) into groups of values, an empty value is returned as 0.
+///
+/// Returns `None` if a value is not a number.
+fn split_parameters(parameters: &str) -> Option<Vec<Vec<u32>>> {
+    parameters
+        .split(';')
+        .map(|group| {
+            group
+                .split(':')
+                .map(|value| {
+                    if value.is_empty() {
+                        Some(0)
+                    } else {
+                        value.parse().ok()
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The modifier bits of the kitty keyboard protocol, also used for the cursor and function keys.
+///
+/// xterm sends Meta in the fourth bit of these keys, which can't be told apart from Super.
+const KITTY_MODIFIERS: &[(u32, KeyModifiers)] = &[
+    (1, KeyModifiers::SHIFT),
+    (2, KeyModifiers::ALT),
+    (4, KeyModifiers::CONTROL),
+    (8, KeyModifiers::SUPER),
+    (16, KeyModifiers::HYPER),
+    (32, KeyModifiers::META),
+    (64, KeyModifiers::CAPS_LOCK),
+    (128, KeyModifiers::NUM_LOCK),
+];
+
+/// The modifier bits of xterm's modifyOtherKeys.
+const XTERM_MODIFIERS: &[(u32, KeyModifiers)] = &[
+    (1, KeyModifiers::SHIFT),
+    (2, KeyModifiers::ALT),
+    (4, KeyModifiers::CONTROL),
+    (8, KeyModifiers::META),
+];
+
+/// Parses the key modifiers and event kind from a `modifiers:kind` parameter group,
+/// `bits` maps the bits of the modifiers to the modifier keys.
+fn parse_modifiers(
+    group: Option<&Vec<u32>>,
+    bits: &[(u32, KeyModifiers)],
+) -> (KeyModifiers, KeyEventKind) {
+    let mask = group
+        .and_then(|group| group.first())
+        .map_or(0, |modifiers| modifiers.saturating_sub(1));
+
+    let mut modifiers = KeyModifiers::empty();
+
+    for &(bit, modifier) in bits {
+        if mask & bit != 0 {
+            modifiers |= modifier;
+        }
+    }
+
+    let kind = match group.and_then(|group| group.get(1)) {
+        Some(2) => KeyEventKind::Repeat,
+        Some(3) => KeyEventKind::Release,
+        _ => KeyEventKind::Press,
+    };
+
+    (modifiers, kind)
+}
+
+/// Parses keys that are encoded as `ESC [ 1 ; modifiers final byte`, e.g. the arrow keys.
+fn parse_modified_key(parameters: &[Vec<u32>], final_byte: u8) -> Option<Event> {
+    let code = match final_byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'S' => KeyCode::F(4),
+        b'Z' => KeyCode::BackTab,
+        // `ESC [ 1 ; modifiers R` (F3) can't be told apart from a cursor position report,
+        // terminals that support the kitty protocol send `ESC [ 13 ~` instead.
+        _ => return None,
+    };
+
+    let (modifiers, kind) = parse_modifiers(parameters.get(1), KITTY_MODIFIERS);
+
+    Some(key_with_kind(code, modifiers, kind))
+}
+
+/// Parses keys that are encoded as `ESC [ number ; modifiers ~`, e.g. the function keys.
+fn parse_special_key(parameters: &[Vec<u32>]) -> Option<Event> {
+    let code = match parameters.first()?.first()? {
+        27 => return parse_modify_other_keys(parameters),
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        number @ 11..=15 => KeyCode::F(*number as u8 - 10),
+        number @ 17..=21 => KeyCode::F(*number as u8 - 11),
+        number @ 23..=26 => KeyCode::F(*number as u8 - 12),
+        number @ 28..=29 => KeyCode::F(*number as u8 - 13),
+        number @ 31..=34 => KeyCode::F(*number as u8 - 14),
+        _ => return None,
+    };
+
+    let (modifiers, kind) = parse_modifiers(parameters.get(1), KITTY_MODIFIERS);
+
+    Some(key_with_kind(code, modifiers, kind))
+}
+
+/// Parses keys of xterm's modifyOtherKeys: `ESC [ 27 ; modifiers ; code ~`.
+fn parse_modify_other_keys(parameters: &[Vec<u32>]) -> Option<Event> {
+    let code = match parameters.get(2)?.first()? {
+        8 | 127 => KeyCode::Backspace,
+        9 => KeyCode::Tab,
+        13 => KeyCode::Enter,
+        27 => KeyCode::Esc,
+        &code => KeyCode::Char(std::char::from_u32(code)?),
+    };
+
+    let (modifiers, kind) = parse_modifiers(parameters.get(1), XTERM_MODIFIERS);
+
+    Some(key_with_kind(code, modifiers, kind))
+}
+
+/// Parses keys of the kitty keyboard protocol: `ESC [ code:shifted ; modifiers:kind u`.
+fn parse_kitty_key(parameters: &[Vec<u32>]) -> Option<Event> {
+    let codes = parameters.first()?;
+    let (mut modifiers, kind) = parse_modifiers(parameters.get(1), KITTY_MODIFIERS);
+
+    let code = match codes.first()? {
+        9 if modifiers.contains(KeyModifiers::SHIFT) => {
+            modifiers.remove(KeyModifiers::SHIFT);
+            KeyCode::BackTab
+        }
+        9 => KeyCode::Tab,
+        13 | 57414 => KeyCode::Enter,
+        27 => KeyCode::Esc,
+        127 => KeyCode::Backspace,
+        // F13-F35
+        code @ 57376..=57398 => KeyCode::F((code - 57376 + 13) as u8),
+        // Keypad digits
+        code @ 57399..=57408 => KeyCode::Char((b'0' + (code - 57399) as u8) as char),
+        57409 => KeyCode::Char('.'),
+        57410 => KeyCode::Char('/'),
+        57411 => KeyCode::Char('*'),
+        57412 => KeyCode::Char('-'),
+        57413 => KeyCode::Char('+'),
+        57415 => KeyCode::Char('='),
+        57416 => KeyCode::Char(','),
+        57417 => KeyCode::Left,
+        57418 => KeyCode::Right,
+        57419 => KeyCode::Up,
+        57420 => KeyCode::Down,
+        57421 => KeyCode::PageUp,
+        57422 => KeyCode::PageDown,
+        57423 => KeyCode::Home,
+        57424 => KeyCode::End,
+        57425 => KeyCode::Insert,
+        57426 => KeyCode::Delete,
+        // Other keys in the private use area (e.g. lock, media, and modifier keys) are not supported.
+        57344..=63743 => return None,
+        &code => {
+            // The shifted key is only reported with `REPORT_ALTERNATE_KEYS`.
+            let code = match codes.get(1) {
+                Some(&shifted) if shifted != 0 && modifiers.contains(KeyModifiers::SHIFT) => {
+                    shifted
+                }
+                _ => code,
+            };
+
+            KeyCode::Char(std::char::from_u32(code)?)
+        }
+    };
+
+    Some(key_with_kind(code, modifiers, kind))
+}
+
+/// Parses a mouse event in the SGR encoding, positions are 1-based.
+fn parse_sgr_mouse(parameters: &str, pressed: bool) -> Option<Event> {
+    let mut values = parameters.split(';').map(|value| value.parse::<u16>());
+
+    let cb = values.next()?.ok()?;
+    let x = values.next()?.ok()?.saturating_sub(1);
+    let y = values.next()?.ok()?.saturating_sub(1);
+
+    parse_mouse(cb, x, y, pressed)
+}
+
+/// Parses a mouse event in the rxvt encoding: `ESC [ Cb ; Cx ; Cy M`.
+fn parse_rxvt_mouse(parameters: &[Vec<u32>]) -> Option<Event> {
+    let value = |index: usize| parameters.get(index)?.first().copied();
+
+    let cb = value(0)?.checked_sub(32)?;
+    let x = value(1)?.saturating_sub(1);
+    let y = value(2)?.saturating_sub(1);
+
+    parse_mouse(cb as u16, x as u16, y as u16, true)
+}
+
+/// Parses the button and modifier code (Cb) of a mouse event at the given 0-based position.
+///
+/// `pressed` is `false` when the encoding reports the release separately (SGR).
+fn parse_mouse(cb: u16, x: u16, y: u16, pressed: bool) -> Option<Event> {
+    let mut modifiers = KeyModifiers::empty();
+
+    if cb & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if cb & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if cb & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let button = match cb & 0b11 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        // Older encodings don't report which button was released.
+        _ => MouseButton::Unknown,
+    };
+
+    let event = if cb & 64 != 0 {
+        match cb & 0b11 {
+            0 => MouseEvent::ScrollUp(x, y, modifiers),
+            1 => MouseEvent::ScrollDown(x, y, modifiers),
+            // Horizontal scrolling is not supported.
+            _ => return None,
+        }
+    } else if cb & 32 != 0 {
+        if button == MouseButton::Unknown {
+            // Moving the mouse without a pressed button is not supported.
+            return None;
+        }
+        MouseEvent::Drag(button, x, y, modifiers)
+    } else if !pressed || button == MouseButton::Unknown {
+        MouseEvent::Up(button, x, y, modifiers)
+    } else {
+        MouseEvent::Down(button, x, y, modifiers)
+    };
+
+    Some(Event::Mouse(event))
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+fn key_with_kind(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> Event {
+    Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        parse_background_color, parse_cell_pixel_size, parse_clipboard, parse_csi,
+        parse_cursor_position, parse_foreground_color, parse_mode_status, parse_palette_color,
+        parse_terminal_identity, parse_window_pixel_size, Parsed, Parser, MAX_STRING_LENGTH,
+    };
+    use crate::{
+        Color, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, ModeStatus, MouseButton,
+        MouseEvent, TerminalIdentity,
+    };
+
+    fn parse(bytes: &[u8]) -> Vec<Parsed> {
+        let mut parser = Parser::default();
+        parser.advance(bytes, false);
+        parser.collect()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_parse_focus_change() {
+        assert_eq!(parse_csi(b"\x1B[I"), Some(Event::FocusGained));
+        assert_eq!(parse_csi(b"\x1B[O"), Some(Event::FocusLost));
+        assert_eq!(parse_csi(b"\x1B[X"), None);
+    }
+
+    #[test]
+    fn test_parse_esc_and_alt() {
+        assert_eq!(
+            parse(b"\x1B"),
+            vec![Parsed::Event(key(KeyCode::Esc, KeyModifiers::empty()))]
+        );
+        assert_eq!(
+            parse(b"\x1Ba"),
+            vec![Parsed::Event(key(KeyCode::Char('a'), KeyModifiers::ALT))]
+        );
+        assert_eq!(
+            parse(b"\x1B]"),
+            vec![Parsed::Event(key(KeyCode::Char(']'), KeyModifiers::ALT))]
+        );
+        assert_eq!(
+            parse(b"\x1BO"),
+            vec![Parsed::Event(key(KeyCode::Char('O'), KeyModifiers::ALT))]
+        );
+        assert_eq!(
+            parse(b"\x1BOQ"),
+            vec![Parsed::Event(key(KeyCode::F(2), KeyModifiers::empty()))]
+        );
+        assert_eq!(
+            parse(b"\x1B\x1B"),
+            vec![
+                Parsed::Event(key(KeyCode::Esc, KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Esc, KeyModifiers::empty()))
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1B\x1B[A"),
+            vec![
+                Parsed::Event(key(KeyCode::Esc, KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Up, KeyModifiers::empty()))
+            ]
+        );
+        assert_eq!(
+            parse(b"\x09\x1B[27u"),
+            vec![
+                Parsed::Event(key(KeyCode::Tab, KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Esc, KeyModifiers::empty()))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_kitty_keys() {
+        assert_eq!(
+            parse_csi(b"\x1B[105;5u"),
+            Some(key(KeyCode::Char('i'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[97:65;2u"),
+            Some(key(KeyCode::Char('A'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[97;1:3u"),
+            Some(Event::Key(KeyEvent {
+                code: KeyCode::Char('a'),
+                modifiers: KeyModifiers::empty(),
+                kind: KeyEventKind::Release,
+            }))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[1;3:2A"),
+            Some(Event::Key(KeyEvent {
+                code: KeyCode::Up,
+                modifiers: KeyModifiers::ALT,
+                kind: KeyEventKind::Repeat,
+            }))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[3;5:3~"),
+            Some(Event::Key(KeyEvent {
+                code: KeyCode::Delete,
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Release,
+            }))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[115;9u"),
+            Some(key(KeyCode::Char('s'), KeyModifiers::SUPER))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[97;113u"),
+            Some(key(
+                KeyCode::Char('a'),
+                KeyModifiers::HYPER | KeyModifiers::META | KeyModifiers::CAPS_LOCK
+            ))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[1;130B"),
+            Some(key(
+                KeyCode::Down,
+                KeyModifiers::SHIFT | KeyModifiers::NUM_LOCK
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_modify_other_keys() {
+        assert_eq!(
+            parse_csi(b"\x1B[27;6;65~"),
+            Some(key(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[27;5;49~"),
+            Some(key(KeyCode::Char('1'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[27;3;13~"),
+            Some(key(KeyCode::Enter, KeyModifiers::ALT))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[27;13;9~"),
+            Some(key(
+                KeyCode::Tab,
+                KeyModifiers::CONTROL | KeyModifiers::META
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_mouse() {
+        assert_eq!(
+            parse_csi(b"\x1B[<0;10;5M"),
+            Some(Event::Mouse(MouseEvent::Down(
+                MouseButton::Left,
+                9,
+                4,
+                KeyModifiers::empty()
+            )))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[<18;1;1m"),
+            Some(Event::Mouse(MouseEvent::Up(
+                MouseButton::Right,
+                0,
+                0,
+                KeyModifiers::CONTROL
+            )))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[M`!!"),
+            Some(Event::Mouse(MouseEvent::ScrollUp(
+                0,
+                0,
+                KeyModifiers::empty()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_paste() {
+        let mut parser = Parser::default();
+        parser.advance(b"\x1B[200~foo\r", true);
+        parser.advance(b"bar\x1B[201~a", false);
+
+        assert_eq!(
+            parser.collect::<Vec<_>>(),
+            vec![
+                Parsed::Event(Event::Paste("foo\rbar".to_string())),
+                Parsed::Event(key(KeyCode::Char('a'), KeyModifiers::empty()))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_typed_string_prefix() {
+        let mut parser = Parser::default();
+        parser.advance(b"\x1B]abc", true);
+        parser.advance(b"def\r", false);
+
+        let mut expected = vec![Parsed::Event(key(KeyCode::Char(']'), KeyModifiers::ALT))];
+        expected.extend(
+            "abcdef"
+                .chars()
+                .map(|c| Parsed::Event(key(KeyCode::Char(c), KeyModifiers::empty()))),
+        );
+        expected.push(Parsed::Event(key(KeyCode::Enter, KeyModifiers::empty())));
+        assert_eq!(parser.collect::<Vec<_>>(), expected);
+
+        assert_eq!(
+            parse(b"\x1BPx\x1B[A"),
+            vec![
+                Parsed::Event(key(KeyCode::Char('P'), KeyModifiers::ALT)),
+                Parsed::Event(key(KeyCode::Char('x'), KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Up, KeyModifiers::empty())),
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1B]1;\r"),
+            vec![
+                Parsed::Event(key(KeyCode::Char(']'), KeyModifiers::ALT)),
+                Parsed::Event(key(KeyCode::Char('1'), KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Char(';'), KeyModifiers::empty())),
+                Parsed::Event(key(KeyCode::Enter, KeyModifiers::empty())),
+            ]
+        );
+
+        // A new escape sequence aborts an incomplete response.
+        let parsed = parse(b"\x1B]11;rgb\x1B[B");
+        assert_eq!(parsed.len(), 8);
+        assert_eq!(
+            parsed.last(),
+            Some(&Parsed::Event(key(KeyCode::Down, KeyModifiers::empty())))
+        );
+
+        let mut unterminated = b"\x1B]52;c;".to_vec();
+        unterminated.resize(MAX_STRING_LENGTH + 1, b'a');
+        assert_eq!(
+            parse(&unterminated).first(),
+            Some(&Parsed::Event(key(KeyCode::Char(']'), KeyModifiers::ALT)))
+        );
+
+        assert_eq!(
+            parse(b"\x1BP>|kitty(0.31.0)\x1B\\"),
+            vec![Parsed::Response(b"\x1BP>|kitty(0.31.0)\x1B\\".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        assert_eq!(
+            parse(b"a\x1B]52;c;Zm9v\x07\x1B[?1;2c\x1B[12;40R"),
+            vec![
+                Parsed::Event(key(KeyCode::Char('a'), KeyModifiers::empty())),
+                Parsed::Response(b"\x1B]52;c;Zm9v\x07".to_vec()),
+                Parsed::Response(b"\x1B[?1;2c".to_vec()),
+                Parsed::Response(b"\x1B[12;40R".to_vec()),
+            ]
+        );
+
+        assert_eq!(parse_cursor_position(b"\x1B[12;40R"), Some((39, 11)));
+        assert_eq!(parse_cursor_position(b"\x1B[?12;40R"), None);
+    }
+
+    #[test]
+    fn test_parse_clipboard() {
+        assert_eq!(
+            parse_clipboard(b"\x1B]52;c;Zm9vYmFy\x1B\\"),
+            Some("foobar".to_string())
+        );
+        assert_eq!(parse_clipboard(b"\x1B]52;p;\x07"), Some(String::new()));
+        assert_eq!(parse_clipboard(b"\x1B]11;rgb:0/0/0\x07"), None);
+    }
+
+    #[test]
+    fn test_parse_default_colors() {
+        assert_eq!(
+            parse_foreground_color(b"\x1B]10;rgb:ffff/8080/0000\x1B\\"),
+            Some(Color::Rgb(255, 128, 0))
+        );
+        assert_eq!(
+            parse_background_color(b"\x1B]11;rgb:1e/1e/2e\x07"),
+            Some(Color::Rgb(30, 30, 46))
+        );
+        assert_eq!(
+            parse_background_color(b"\x1B]11;rgba:f/8/0/f\x07"),
+            Some(Color::Rgb(255, 136, 0))
+        );
+        assert_eq!(parse_foreground_color(b"\x1B]11;rgb:0/0/0\x07"), None);
+        assert_eq!(parse_background_color(b"\x1B]11;rgb:0/0\x07"), None);
+        assert_eq!(parse_background_color(b"\x1B]11;rgb:00000/0/0\x07"), None);
+    }
+
+    #[test]
+    fn test_parse_terminal_identity() {
+        let mut identity = TerminalIdentity::default();
+
+        assert!(!parse_terminal_identity(
+            &mut identity,
+            b"\x1BP>|kitty(0.31.0)\x1B\\"
+        ));
+        assert!(!parse_terminal_identity(&mut identity, b"\x1B[>1;4000;29c"));
+        assert!(!parse_terminal_identity(&mut identity, b"\x1B[?62;1u"));
+        assert!(parse_terminal_identity(&mut identity, b"\x1B[?62;4;22c"));
+
+        assert_eq!(
+            identity,
+            TerminalIdentity {
+                name: Some("kitty".to_string()),
+                version: Some("0.31.0".to_string()),
+                device_type: Some(1),
+                firmware_version: Some(4000),
+                features: vec![62, 4, 22],
+            }
+        );
+        assert!(identity.supports_sixel());
+
+        let mut identity = TerminalIdentity::default();
+        parse_terminal_identity(
+            &mut identity,
+            b"\x1BP>|WezTerm 20240203-110809-5046fc22\x1B\\",
+        );
+        assert_eq!(identity.name.as_deref(), Some("WezTerm"));
+        assert_eq!(
+            identity.version.as_deref(),
+            Some("20240203-110809-5046fc22")
+        );
+
+        parse_terminal_identity(&mut identity, b"\x1BP>|foot\x1B\\");
+        assert_eq!(identity.name.as_deref(), Some("foot"));
+        assert_eq!(identity.version, None);
+
+        assert!(parse_terminal_identity(&mut identity, b"\x1B[?1;2c"));
+        assert!(!identity.supports_sixel());
+    }
+
+    #[test]
+    fn test_parse_pixel_size() {
+        assert_eq!(
+            parse_window_pixel_size(b"\x1B[4;768;1024t"),
+            Some((1024, 768))
+        );
+        assert_eq!(parse_cell_pixel_size(b"\x1B[6;20;10t"), Some((10, 20)));
+        assert_eq!(parse_cell_pixel_size(b"\x1B[4;768;1024t"), None);
+        assert_eq!(parse_window_pixel_size(b"\x1B[8;24;80t"), None);
+    }
+
+    #[test]
+    fn test_parse_mode_status() {
+        assert_eq!(
+            parse(b"\x1B[?2026;2$y"),
+            vec![Parsed::Response(b"\x1B[?2026;2$y".to_vec())]
+        );
+
+        for (response, status) in &[
+            (&b"\x1B[?2026;0$y"[..], ModeStatus::NotRecognized),
+            (b"\x1B[?2026;1$y", ModeStatus::Set),
+            (b"\x1B[?2026;2$y", ModeStatus::Reset),
+            (b"\x1B[?2026;3$y", ModeStatus::PermanentlySet),
+            (b"\x1B[?2026;4$y", ModeStatus::PermanentlyReset),
+        ] {
+            assert_eq!(parse_mode_status(response, 2026), Some(*status));
+        }
+
+        assert_eq!(parse_mode_status(b"\x1B[?2004;1$y", 2026), None);
+        assert_eq!(parse_mode_status(b"\x1B[2026;1$y", 2026), None);
+    }
+
+    #[test]
+    fn test_parse_palette_color() {
+        assert_eq!(
+            parse_palette_color(b"\x1B]4;1;rgb:cdcd/0000/0000\x1B\\", 1),
+            Some(Color::Rgb(205, 0, 0))
+        );
+        assert_eq!(
+            parse_palette_color(b"\x1B]4;12;rgb:0/0/f\x07", 12),
+            Some(Color::Rgb(0, 0, 255))
+        );
+        assert_eq!(parse_palette_color(b"\x1B]4;12;rgb:0/0/f\x07", 1), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! Reads and changes the input mode with the terminal settings (termios) of the terminal device.
+
+use std::io;
+
+use crate::InputMode;
+
+/// Returns the current input mode of the terminal.
+#[cfg(unix)]
+pub(crate) fn get() -> io::Result<InputMode> {
+    let termios = unix::get_termios()?;
+
+    let mut mode = InputMode::empty();
+    mode.set(InputMode::ECHO, termios.c_lflag & libc::ECHO != 0);
+    mode.set(InputMode::CANONICAL, termios.c_lflag & libc::ICANON != 0);
+    mode.set(InputMode::SIGNALS, termios.c_lflag & libc::ISIG != 0);
+    mode.set(
+        InputMode::OUTPUT_PROCESSING,
+        termios.c_oflag & libc::OPOST != 0,
+    );
+
+    Ok(mode)
+}
+
+/// Changes the input mode of the terminal, the other settings are kept.
+#[cfg(unix)]
+pub(crate) fn set(mode: InputMode) -> io::Result<()> {
+    let mut termios = unix::get_termios()?;
+
+    let set_flag = |flags: &mut libc::tcflag_t, flag: libc::tcflag_t, enabled: bool| {
+        if enabled {
+            *flags |= flag;
+        } else {
+            *flags &= !flag;
+        }
+    };
+
+    set_flag(
+        &mut termios.c_lflag,
+        libc::ECHO,
+        mode.contains(InputMode::ECHO),
+    );
+    set_flag(
+        &mut termios.c_lflag,
+        libc::ICANON,
+        mode.contains(InputMode::CANONICAL),
+    );
+    set_flag(
+        &mut termios.c_lflag,
+        libc::ISIG,
+        mode.contains(InputMode::SIGNALS),
+    );
+    set_flag(
+        &mut termios.c_oflag,
+        libc::OPOST,
+        mode.contains(InputMode::OUTPUT_PROCESSING),
+    );
+
+    if !mode.contains(InputMode::CANONICAL) {
+        // Return from a read as soon as a single byte is available.
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+    }
+
+    unix::set_termios(&termios)
+}
+
+/// Input modes are only supported on UNIX systems.
+#[cfg(not(unix))]
+pub(crate) fn get() -> io::Result<InputMode> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Input modes are only supported on UNIX systems.",
+    ))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{fs::File, io, mem, os::unix::io::AsRawFd};
+
+    // The settings belong to the terminal device, stdin may be redirected.
+    fn tty() -> io::Result<File> {
+        File::open("/dev/tty")
+    }
+
+    pub(super) fn get_termios() -> io::Result<libc::termios> {
+        let tty = tty()?;
+
+        unsafe {
+            let mut termios: libc::termios = mem::zeroed();
+
+            if libc::tcgetattr(tty.as_raw_fd(), &mut termios) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(termios)
+        }
+    }
+
+    pub(super) fn set_termios(termios: &libc::termios) -> io::Result<()> {
+        let tty = tty()?;
+
+        if unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, termios) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
@@ -9,6 +9,30 @@ pub(crate) use self::crossterm::BackendImpl;
 #[cfg(feature = "termion-backend")]
 pub(crate) use self::termion::BackendImpl;
 
+// Not every backend needs every escape sequence.
+#[allow(dead_code)]
+mod ansi;
+
+mod base64;
+
+mod environment;
+
+// The curses backend asks curses for the colors instead.
+#[cfg_attr(feature = "crosscurses-backend", allow(dead_code))]
+mod color_support;
+
+// The curses backend changes the input mode through curses.
+#[cfg_attr(feature = "crosscurses-backend", allow(dead_code))]
+mod input_mode;
+
+// Responses to queries are only read on UNIX systems.
+#[cfg_attr(not(unix), allow(dead_code))]
+mod input;
+
+// The termion backend reads responses to queries on its input thread instead.
+#[cfg_attr(feature = "termion-backend", allow(dead_code))]
+mod query;
+
 #[cfg(feature = "crossterm-backend")]
 mod crossterm;
 
@@ -28,4 +52,6 @@ pub trait Backend<W: Write> {
     fn batch(&mut self, action: Action) -> error::Result<()>;
     fn flush_batch(&mut self) -> error::Result<()>;
     fn get(&self, retrieve_operation: Value) -> error::Result<Retrieved>;
+    /// Returns whether the output is a terminal if the backend doesn't write it to the buffer.
+    fn output_is_tty(&self) -> Option<bool>;
 }
@@ -0,0 +1,230 @@
+//! Queries that the terminal answers with an escape sequence on its input.
+
+#[cfg(unix)]
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    mem,
+    os::unix::io::AsRawFd,
+    time::Instant,
+};
+
+#[cfg(unix)]
+use crate::backend::input::{Parsed, Parser};
+use crate::{
+    backend::{ansi, input},
+    error, Color, Event, Mode, ModeStatus, TerminalIdentity,
+};
+
+/// Writes a query to the terminal and reads the response, which is decoded with `parse`.
+///
+/// Returns `None` if the terminal didn't answer in time.
+/// Events that are read while waiting for the response are passed to `on_event`.
+#[cfg(unix)]
+pub(crate) fn query<T>(
+    query: &str,
+    mut parse: impl FnMut(&[u8]) -> Option<T>,
+    mut on_event: impl FnMut(Event),
+) -> error::Result<Option<T>> {
+    // The query is written to the terminal directly,
+    // because the buffer of the backend doesn't have to be the terminal.
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+
+    // Without raw mode the response is echoed and can't be read until a new line is entered.
+    let _raw_mode = RawMode::enable(&tty)?;
+
+    tty.write_all(query.as_bytes())?;
+    tty.flush()?;
+
+    let deadline = Instant::now() + ansi::QUERY_TIMEOUT;
+    let mut parser = Parser::default();
+    let mut bytes = [0; 1024];
+    let mut response = None;
+
+    while response.is_none() && poll(&tty, deadline)? {
+        let count = tty.read(&mut bytes)?;
+        parser.advance(&bytes[..count], false);
+
+        for parsed in &mut parser {
+            match parsed {
+                Parsed::Event(event) => on_event(event),
+                Parsed::Response(bytes) if response.is_none() => response = parse(&bytes),
+                Parsed::Response(_) => {}
+            }
+        }
+    }
+
+    Ok(response)
+}
+
+/// Responses to queries can't be read on Windows.
+#[cfg(not(unix))]
+pub(crate) fn query<T>(
+    _query: &str,
+    _parse: impl FnMut(&[u8]) -> Option<T>,
+    _on_event: impl FnMut(Event),
+) -> error::Result<Option<T>> {
+    Ok(None)
+}
+
+/// The `query` function of a backend, which writes a query and decodes the response with a parser.
+pub(crate) trait Query<T>:
+    FnMut(&str, &mut dyn FnMut(&[u8]) -> Option<T>) -> error::Result<Option<T>>
+{
+}
+
+impl<T, F> Query<T> for F where
+    F: FnMut(&str, &mut dyn FnMut(&[u8]) -> Option<T>) -> error::Result<Option<T>>
+{
+}
+
+/// Queries the default foreground and background color.
+pub(crate) fn default_colors(
+    mut query: impl Query<Color>,
+) -> error::Result<(Option<Color>, Option<Color>)> {
+    let foreground = query(
+        ansi::QUERY_FOREGROUND_COLOR,
+        &mut input::parse_foreground_color,
+    )?;
+
+    // A terminal that doesn't answer the first query won't answer the second one,
+    // so don't wait for it a second time.
+    let background = match foreground {
+        Some(_) => query(
+            ansi::QUERY_BACKGROUND_COLOR,
+            &mut input::parse_background_color,
+        )?,
+        None => None,
+    };
+
+    Ok((foreground, background))
+}
+
+/// Queries the name, version and device attributes of the terminal.
+pub(crate) fn terminal_identity(
+    mut query: impl Query<()>,
+) -> error::Result<Option<TerminalIdentity>> {
+    let mut identity = TerminalIdentity::default();
+
+    let answered = query(ansi::QUERY_TERMINAL_IDENTITY, &mut |response| {
+        input::parse_terminal_identity(&mut identity, response).then_some(())
+    })?;
+
+    Ok(answered.map(|()| identity))
+}
+
+/// Returns the size of the window in pixels, as `(width, height)`.
+///
+/// `size` is the pixel size of the backend, the terminal is asked instead if it isn't known.
+pub(crate) fn window_pixel_size(
+    size: Option<(u16, u16)>,
+    mut query: impl Query<(u16, u16)>,
+) -> error::Result<Option<(u16, u16)>> {
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => Ok(Some((width, height))),
+        // Not every terminal fills in the pixel size, ask the terminal itself instead.
+        _ => query(
+            ansi::QUERY_WINDOW_PIXEL_SIZE,
+            &mut input::parse_window_pixel_size,
+        ),
+    }
+}
+
+/// Returns the size of a cell in pixels, as `(width, height)`.
+///
+/// `size` is the pixel size and `cells` the number of columns and rows of the backend,
+/// the terminal is asked instead if either isn't known.
+pub(crate) fn cell_pixel_size(
+    size: Option<(u16, u16)>,
+    cells: Option<(u16, u16)>,
+    mut query: impl Query<(u16, u16)>,
+) -> error::Result<Option<(u16, u16)>> {
+    match (size, cells) {
+        (Some((width, height)), Some((columns, rows)))
+            if width > 0 && height > 0 && columns > 0 && rows > 0 =>
+        {
+            Ok(Some((width / columns, height / rows)))
+        }
+        _ => query(
+            ansi::QUERY_CELL_PIXEL_SIZE,
+            &mut input::parse_cell_pixel_size,
+        ),
+    }
+}
+
+/// Queries whether a mode is set.
+pub(crate) fn mode_status(
+    mode: Mode,
+    mut query: impl Query<ModeStatus>,
+) -> error::Result<Option<ModeStatus>> {
+    query(&ansi::query_mode(mode), &mut |response| {
+        input::parse_mode_status(response, ansi::mode_number(mode))
+    })
+}
+
+/// Queries the color of an entry in the palette of the terminal.
+pub(crate) fn palette_color(
+    index: u8,
+    mut query: impl Query<Color>,
+) -> error::Result<Option<Color>> {
+    query(&ansi::query_palette_color(index), &mut |response| {
+        input::parse_palette_color(response, index)
+    })
+}
+
+/// Waits until input is available, returns `false` if the deadline has passed.
+#[cfg(unix)]
+fn poll(tty: &File, deadline: Instant) -> io::Result<bool> {
+    let timeout = deadline.saturating_duration_since(Instant::now());
+
+    let mut fd = libc::pollfd {
+        fd: tty.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    match unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) } {
+        -1 => Err(io::Error::last_os_error()),
+        count => Ok(count > 0),
+    }
+}
+
+/// Enables raw mode and restores the previous terminal settings when dropped.
+#[cfg(unix)]
+struct RawMode {
+    fd: libc::c_int,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    fn enable(tty: &File) -> io::Result<RawMode> {
+        let fd = tty.as_raw_fd();
+
+        unsafe {
+            let mut original: libc::termios = mem::zeroed();
+
+            if libc::tcgetattr(fd, &mut original) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+
+            if libc::tcsetattr(fd, libc::TCSANOW, &raw) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(RawMode { fd, original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.original);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 use std::{
+    fs::File,
+    mem,
+    os::unix::io::AsRawFd,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -9,17 +12,33 @@ use std::{
 use crossbeam_channel::Sender;
 use signal_hook::iterator::Signals;
 
-/// This starts a new thread to listen for SIGWINCH signals
+/// This starts a new thread to listen for SIGWINCH signals and report the new terminal size (columns, rows)
 #[allow(unused)]
-pub fn start_resize_thread(resize_sender: Sender<()>, resize_running: Arc<AtomicBool>) {
+pub fn start_resize_thread(resize_sender: Sender<(u16, u16)>, resize_running: Arc<AtomicBool>) {
     let signals = Signals::new(&[libc::SIGWINCH]).unwrap();
     thread::spawn(move || {
         // This thread will listen to SIGWINCH events and report them.
         while resize_running.load(Ordering::Relaxed) {
             // We know it will only contain SIGWINCH signals, so no need to check.
             if signals.wait().count() > 0 {
-                resize_sender.send(()).unwrap();
+                // The size can't be read when the terminal went away, there is nothing to report then.
+                if let Some(size) = terminal_size() {
+                    resize_sender.send(size).unwrap();
+                }
             }
         }
     });
 }
+
+/// Returns the size of the terminal (columns, rows).
+fn terminal_size() -> Option<(u16, u16)> {
+    // termion asks stdout for the size, which isn't the terminal if output is redirected.
+    let tty = File::open("/dev/tty").ok()?;
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+
+    if unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == -1 {
+        return None;
+    }
+
+    Some((size.ws_col, size.ws_row))
+}
@@ -3,25 +3,29 @@ use std::{
     fmt::{Display, Formatter},
     fs::File,
     io,
-    io::Write,
+    io::{Read, Write},
     result,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
     thread,
+    time::Instant,
 };
 
 use crossbeam_channel::{select, unbounded, Receiver};
 use termion::{
     clear, color, cursor, get_tty,
-    input::TermRead,
     raw::{IntoRawMode, RawTerminal},
-    screen, style, terminal_size,
+    screen, style, terminal_size, terminal_size_pixels,
 };
 
 use crate::{
-    backend::{resize, termion::cursor::position, Backend},
+    backend::{
+        ansi, color_support, environment, input,
+        input::{Parsed, Parser},
+        input_mode, query, resize, Backend,
+    },
     error,
     error::ErrorKind,
     Action, Attribute, Clear, Color, Event, Retrieved, Value,
@@ -66,12 +70,52 @@ pub struct BackendImpl<W: Write> {
     buffer: W,
 
     input_receiver: Option<Receiver<Event>>,
-    resize_receiver: Option<Receiver<()>>,
+    resize_receiver: Option<Receiver<(u16, u16)>>,
+    // Responses to queries are read by the input thread as well.
+    response_receiver: Option<Receiver<Vec<u8>>>,
 
     is_raw_mode_enabled: bool,
 }
 
 impl<W: Write> BackendImpl<W> {
+    /// Queries the terminal, returns `None` if it doesn't answer in time.
+    fn query<T>(
+        &self,
+        query: &str,
+        mut parse: impl FnMut(&[u8]) -> Option<T>,
+    ) -> error::Result<Option<T>> {
+        let response_receiver = match self.response_receiver {
+            Some(ref response_receiver) => response_receiver,
+            None => return Ok(None),
+        };
+
+        // Responses to earlier queries that arrived too late are dropped.
+        response_receiver.try_iter().for_each(drop);
+
+        // Without raw mode the response is echoed and can't be read until a new line is entered.
+        let _raw_mode = if self.is_raw_mode_enabled {
+            None
+        } else {
+            Some(get_tty()?.into_raw_mode()?)
+        };
+
+        let mut tty = get_tty()?;
+        tty.write_all(query.as_bytes())?;
+        tty.flush()?;
+
+        let deadline = Instant::now() + ansi::QUERY_TIMEOUT;
+
+        while let Ok(response) =
+            response_receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            if let Some(value) = parse(&response) {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Write the given color to the given buffer.
     pub fn w_color<T: color::Color>(&mut self, color: T, is_fg: bool) -> io::Result<()> {
         if let Some(ref mut terminal) = self.raw_buffer {
@@ -140,6 +184,10 @@ impl<W: Write> BackendImpl<W> {
 
             Attribute::Underlined => self.w_display(&style::Underline)?,
             Attribute::UnderlinedOff => self.w_display(&style::NoUnderline)?,
+            Attribute::DoubleUnderlined => self.w_display(&ansi::DOUBLE_UNDERLINED)?,
+            Attribute::Undercurled => self.w_display(&ansi::UNDERCURLED)?,
+            Attribute::Underdotted => self.w_display(&ansi::UNDERDOTTED)?,
+            Attribute::Underdashed => self.w_display(&ansi::UNDERDASHED)?,
 
             Attribute::Reset => self.w_display(&style::Reset)?,
             _ => {
@@ -158,6 +206,7 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
     fn create(buffer: W) -> Self {
         let (input_sender, input_receiver) = unbounded::<Event>();
         let (resize_sender, resize_receiver) = unbounded();
+        let (response_sender, response_receiver) = unbounded::<Vec<u8>>();
 
         let running = Arc::new(AtomicBool::new(true));
 
@@ -166,14 +215,31 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
 
         // termion is blocking by default, read input from a separate thread.
         thread::spawn(move || {
-            let input = termion::get_tty().unwrap();
-            let mut events = input.events();
+            let mut input = termion::get_tty().unwrap();
+
+            // termion's own parser doesn't know bracketed paste and the kitty keyboard protocol,
+            // the raw bytes are decoded with the shared parser instead.
+            let mut parser = Parser::default();
+            let mut bytes = [0; 1024];
 
-            while let Some(Ok(event)) = events.next() {
-                // If we can't send, then receiving side closed, stop thread.
-                if input_sender.send(Event::from(event)).is_err() {
+            'input: while let Ok(count) = input.read(&mut bytes) {
+                if count == 0 {
                     break;
                 }
+
+                parser.advance(&bytes[..count], false);
+
+                for parsed in &mut parser {
+                    let sent = match parsed {
+                        Parsed::Event(event) => input_sender.send(event).is_ok(),
+                        Parsed::Response(response) => response_sender.send(response).is_ok(),
+                    };
+
+                    // If we can't send, then receiving side closed, stop thread.
+                    if !sent {
+                        break 'input;
+                    }
+                }
             }
 
             running.store(false, Ordering::Relaxed);
@@ -184,6 +250,7 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
             buffer,
             resize_receiver: Some(resize_receiver),
             input_receiver: Some(input_receiver),
+            response_receiver: Some(response_receiver),
             is_raw_mode_enabled: false,
         }
     }
@@ -210,10 +277,23 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 Clear::CurrentLine => self.w_display(&clear::CurrentLine)?,
                 Clear::UntilNewLine => self.w_display(&clear::UntilNewline)?,
             },
+            Action::DisableLineWrap => self.w_display(&ansi::DISABLE_LINE_WRAP)?,
+            Action::EnableLineWrap => self.w_display(&ansi::ENABLE_LINE_WRAP)?,
+            Action::Bell => self.w_display(&ansi::BELL)?,
+            Action::Flash => {
+                self.w_display(&ansi::ENABLE_REVERSE_SCREEN)?;
+                self.flush_batch()?;
+                thread::sleep(ansi::FLASH_DURATION);
+                self.w_display(&ansi::DISABLE_REVERSE_SCREEN)?;
+                self.flush_batch()?;
+            }
             Action::EnterAlternateScreen => self.w_display(&screen::ToAlternateScreen)?,
             Action::LeaveAlternateScreen => self.w_display(&screen::ToMainScreen)?,
             Action::SetForegroundColor(color) => self.f_color(color, true)?,
             Action::SetBackgroundColor(color) => self.f_color(color, false)?,
+            Action::SetUnderlineColor(color) => {
+                self.w_display(&ansi::set_underline_color(color))?
+            }
             Action::SetAttribute(attr) => self.f_attribute(attr)?,
             Action::ResetColor => self.w_display(&format!(
                 "{}{}",
@@ -230,12 +310,62 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                     self.is_raw_mode_enabled = false;
                 }
             }
+            Action::SetInputMode(mode) => input_mode::set(mode)?,
+            Action::ResetTerminal | Action::SoftReset => {
+                // Not every terminal disables mouse event capturing on a soft reset.
+                self.buffer.write_all(DISABLE_MOUSE_CAPTURE.as_bytes())?;
+
+                match action {
+                    Action::ResetTerminal => self.w_display(&ansi::RESET_TERMINAL)?,
+                    _ => self.w_display(&ansi::SOFT_RESET)?,
+                };
+
+                // Dropping the raw terminal restores the original terminal mode.
+                self.raw_buffer = None;
+                self.is_raw_mode_enabled = false;
+            }
             Action::EnableMouseCapture => {
                 self.buffer.write_all(ENABLE_MOUSE_CAPTURE.as_bytes())?;
             }
             Action::DisableMouseCapture => {
                 self.buffer.write_all(DISABLE_MOUSE_CAPTURE.as_bytes())?;
             }
+            Action::EnableBracketedPaste => self.w_display(&ansi::ENABLE_BRACKETED_PASTE)?,
+            Action::DisableBracketedPaste => self.w_display(&ansi::DISABLE_BRACKETED_PASTE)?,
+            Action::EnableFocusChange => self.w_display(&ansi::ENABLE_FOCUS_CHANGE)?,
+            Action::DisableFocusChange => self.w_display(&ansi::DISABLE_FOCUS_CHANGE)?,
+            Action::BeginSynchronizedUpdate => self.w_display(&ansi::BEGIN_SYNCHRONIZED_UPDATE)?,
+            Action::EndSynchronizedUpdate => self.w_display(&ansi::END_SYNCHRONIZED_UPDATE)?,
+            Action::PushKeyboardEnhancementFlags(flags) => {
+                self.w_display(&ansi::push_keyboard_enhancement_flags(flags))?
+            }
+            Action::PopKeyboardEnhancementFlags => {
+                self.w_display(&ansi::POP_KEYBOARD_ENHANCEMENT_FLAGS)?
+            }
+            Action::EnableModifyOtherKeys => self.w_display(&ansi::ENABLE_MODIFY_OTHER_KEYS)?,
+            Action::DisableModifyOtherKeys => self.w_display(&ansi::DISABLE_MODIFY_OTHER_KEYS)?,
+            Action::BeginHyperlink { uri, id } => {
+                self.w_display(&ansi::begin_hyperlink(&uri, id.as_deref()))?
+            }
+            Action::EndHyperlink => self.w_display(&ansi::END_HYPERLINK)?,
+            Action::SetClipboard { selection, content } => {
+                self.w_display(&ansi::set_clipboard(selection, &content))?
+            }
+            Action::Notify { title, body } => self.w_display(&ansi::notify(&title, &body))?,
+            Action::MarkPromptStart => self.w_display(&ansi::MARK_PROMPT_START)?,
+            Action::MarkCommandStart => self.w_display(&ansi::MARK_COMMAND_START)?,
+            Action::MarkOutputStart => self.w_display(&ansi::MARK_OUTPUT_START)?,
+            Action::MarkCommandFinished(exit_code) => {
+                self.w_display(&ansi::mark_command_finished(exit_code))?
+            }
+            Action::ReportWorkingDirectory(path) => {
+                self.w_display(&ansi::report_working_directory(&path)?)?
+            }
+            Action::SetPaletteColor(index, color) => match ansi::set_palette_color(index, color) {
+                Some(sequence) => self.w_display(&sequence)?,
+                None => return Err(error::ErrorKind::ActionNotSupported(String::from(action))),
+            },
+            Action::ResetPalette => self.w_display(&ansi::RESET_PALETTE)?,
             Action::SetTerminalSize(..)
             | Action::EnableBlinking
             | Action::DisableBlinking
@@ -261,16 +391,17 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                 Retrieved::TerminalSize(size.0, size.1)
             }
             Value::CursorPosition => {
-                // if raw mode is disabled, we need to enable and disable it.
-                // Otherwise the position is written to the console window.
-                let (x, y) = if self.is_raw_mode_enabled {
-                    position()?
-                } else {
-                    get_tty()?.into_raw_mode()?;
-                    position()?
-                };
-
-                Retrieved::CursorPosition(x, y)
+                // termion's own implementation blocks until the terminal answers
+                // and swallows the input that is typed in the meantime.
+                match self.query(ansi::QUERY_CURSOR_POSITION, input::parse_cursor_position)? {
+                    Some((x, y)) => Retrieved::CursorPosition(x, y),
+                    None => {
+                        return Err(ErrorKind::IoError(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "The terminal didn't report the cursor position in time.",
+                        )))
+                    }
+                }
             }
             Value::Event(duration) => {
                 if let Some(ref input_receiver) = self.input_receiver {
@@ -278,13 +409,13 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
                         let event = if let Some(duration) = duration {
                             select! {
                                recv(input_receiver) -> event => event.ok(),
-                               recv(resize_receiver) -> _ => Some(Event::Resize),
+                               recv(resize_receiver) -> size => size.ok().map(|(columns, rows)| Event::Resize(columns, rows)),
                                default(duration) => None,
                             }
                         } else {
                             select! {
                                recv(input_receiver) -> event => event.ok(),
-                               recv(resize_receiver) -> _ => Some(Event::Resize),
+                               recv(resize_receiver) -> size => size.ok().map(|(columns, rows)| Event::Resize(columns, rows)),
                             }
                         };
                         return Ok(event.map_or(Retrieved::Event(None), |event| {
@@ -295,8 +426,52 @@ impl<W: Write> Backend<W> for BackendImpl<W> {
 
                 Retrieved::Event(None)
             }
+            Value::Clipboard(selection) => Retrieved::Clipboard(
+                self.query(&ansi::query_clipboard(selection), input::parse_clipboard)?,
+            ),
+            Value::ColorSupport => Retrieved::ColorSupport(color_support::detect()),
+            Value::DefaultColors => {
+                let (foreground, background) =
+                    query::default_colors(|query, parse| self.query(query, parse))?;
+
+                Retrieved::DefaultColors {
+                    foreground,
+                    background,
+                }
+            }
+            Value::TerminalIdentity => {
+                let identity = query::terminal_identity(|query, parse| self.query(query, parse))?;
+
+                Retrieved::TerminalIdentity(identity)
+            }
+            Value::WindowPixelSize => Retrieved::WindowPixelSize(query::window_pixel_size(
+                terminal_size_pixels().ok(),
+                |query, parse| self.query(query, parse),
+            )?),
+            Value::CellPixelSize => Retrieved::CellPixelSize(query::cell_pixel_size(
+                terminal_size_pixels().ok(),
+                terminal_size().ok(),
+                |query, parse| self.query(query, parse),
+            )?),
+            Value::ModeStatus(mode) => {
+                Retrieved::ModeStatus(query::mode_status(mode, |query, parse| {
+                    self.query(query, parse)
+                })?)
+            }
+            Value::PaletteColor(index) => {
+                Retrieved::PaletteColor(query::palette_color(index, |query, parse| {
+                    self.query(query, parse)
+                })?)
+            }
+            Value::Environment => Retrieved::Environment(environment::detect()),
+            Value::InputMode => Retrieved::InputMode(input_mode::get()?),
         })
     }
+
+    fn output_is_tty(&self) -> Option<bool> {
+        // In raw mode the output is written to the terminal device.
+        self.raw_buffer.as_ref().map(|_| true)
+    }
 }
 
 impl<W: Write> Write for BackendImpl<W> {
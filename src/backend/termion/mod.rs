@@ -1,5 +1,3 @@
 pub use self::implementation::BackendImpl;
 
-mod cursor;
 mod implementation;
-mod mapping;
@@ -1,7 +1,13 @@
 pub use self::{
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent},
-    style::{Attribute, Color},
-    terminal::Clear,
+    event::{
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        MouseButton, MouseEvent,
+    },
+    style::{Attribute, Color, ColorSupport},
+    terminal::{
+        Clear, ClipboardSelection, Environment, InputMode, IsTty, Mode, ModeStatus, Multiplexer,
+        TerminalIdentity,
+    },
 };
 
 mod event;
@@ -2,14 +2,29 @@ use bitflags::bitflags;
 
 /// Represents an event.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Hash)]
 pub enum Event {
     /// A single key event with additional pressed modifiers.
     Key(KeyEvent),
     /// A singe mouse event with additional pressed modifiers.
     Mouse(MouseEvent),
+    /// Text that was pasted into the terminal.
+    ///
+    /// Only reported when bracketed paste is enabled with
+    /// [Action::EnableBracketedPaste](enum.Action.html#variant.EnableBracketedPaste).
+    Paste(String),
+    /// The terminal window gained focus.
+    ///
+    /// Only reported when focus change reporting is enabled with
+    /// [Action::EnableFocusChange](enum.Action.html#variant.EnableFocusChange).
+    FocusGained,
+    /// The terminal window lost focus.
+    ///
+    /// Only reported when focus change reporting is enabled with
+    /// [Action::EnableFocusChange](enum.Action.html#variant.EnableFocusChange).
+    FocusLost,
     /// An resize event with new dimensions after resize (columns, rows).
-    Resize,
+    Resize(u16, u16),
     /// An event was not supported by the backend.
     Unknown,
 }
@@ -83,15 +98,55 @@ pub enum MouseButton {
 }
 
 bitflags! {
-    /// Represents key modifiers (shift, control, alt).
+    /// Represents key modifiers (shift, control, alt, super, hyper, meta) and lock states.
+    ///
+    /// SUPER, HYPER, META, CAPS_LOCK and NUM_LOCK are only reported by terminals
+    /// that support the kitty keyboard protocol or encode them with xterm's modifyOtherKeys,
+    /// see [Action::PushKeyboardEnhancementFlags](enum.Action.html#variant.PushKeyboardEnhancementFlags).
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct KeyModifiers: u8 {
         const SHIFT = 0b0000_0001;
         const CONTROL = 0b0000_0010;
         const ALT = 0b0000_0100;
+        /// The Super key, e.g. the Windows or Command key.
+        const SUPER = 0b0000_1000;
+        const HYPER = 0b0001_0000;
+        const META = 0b0010_0000;
+        /// Caps Lock was active when the key was pressed.
+        const CAPS_LOCK = 0b0100_0000;
+        /// Num Lock was active when the key was pressed.
+        const NUM_LOCK = 0b1000_0000;
     }
 }
 
+bitflags! {
+    /// Represents the progressive enhancements of the
+    /// [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/).
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyboardEnhancementFlags: u8 {
+        /// Reports keys that are ambiguous in the legacy encoding (e.g. Esc, Alt + key) as escape codes.
+        const DISAMBIGUATE_ESCAPE_CODES = 0b0000_0001;
+        /// Reports key repeat and release events.
+        const REPORT_EVENT_TYPES = 0b0000_0010;
+        /// Reports the shifted key along with the key.
+        const REPORT_ALTERNATE_KEYS = 0b0000_0100;
+        /// Reports all keys as escape codes, including Enter, Tab, Backspace and text keys.
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b0000_1000;
+    }
+}
+
+/// Represents the kind of a key event.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    Press,
+    /// The key is held down and repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
 /// Represents a key event.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
@@ -100,20 +155,26 @@ pub struct KeyEvent {
     pub code: KeyCode,
     /// Additional key modifiers.
     pub modifiers: KeyModifiers,
+    /// The kind of event.
+    ///
+    /// Repeats and releases are only reported when enabled with
+    /// [Action::PushKeyboardEnhancementFlags](enum.Action.html#variant.PushKeyboardEnhancementFlags).
+    pub kind: KeyEventKind,
 }
 
 impl KeyEvent {
     pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
-        KeyEvent { code, modifiers }
+        KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+        }
     }
 }
 
 impl From<KeyCode> for KeyEvent {
     fn from(code: KeyCode) -> Self {
-        KeyEvent {
-            code,
-            modifiers: KeyModifiers::empty(),
-        }
+        KeyEvent::new(code, KeyModifiers::empty())
     }
 }
 
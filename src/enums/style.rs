@@ -87,6 +87,20 @@ impl From<u8> for Color {
     }
 }
 
+/// The colors that a terminal can display.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum ColorSupport {
+    /// No colors, only attributes like bold or reversed.
+    Monochrome,
+    /// The 16 (or only 8) basic colors, e.g. the Linux console.
+    Ansi16,
+    /// The 256 colors of [Color::AnsiValue](enum.Color.html#variant.AnsiValue).
+    Ansi256,
+    /// Any [Color::Rgb](enum.Color.html#variant.Rgb) color.
+    TrueColor,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum Attribute {
@@ -105,8 +119,16 @@ pub enum Attribute {
 
     /// Underlines the text.
     Underlined,
-    /// Turns off the `Underlined` attribute.
+    /// Turns off the `Underlined` attribute and the other underline styles.
     UnderlinedOff,
+    /// Underlines the text with two lines.
+    DoubleUnderlined,
+    /// Underlines the text with a curly line.
+    Undercurled,
+    /// Underlines the text with a dotted line.
+    Underdotted,
+    /// Underlines the text with a dashed line.
+    Underdashed,
 
     /// Makes the text blinking (< 150 per minute).
     SlowBlink,
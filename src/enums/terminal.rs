@@ -1,3 +1,5 @@
+use bitflags::bitflags;
+
 /// Different ways to clear the terminal buffer.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
@@ -13,3 +15,148 @@ pub enum Clear {
     /// All cells from the cursor position until the new line.
     UntilNewLine,
 }
+
+/// A selection of the system clipboard.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum ClipboardSelection {
+    /// The clipboard that is used by copy and paste.
+    Clipboard,
+    /// The primary selection, which is pasted with the middle mouse button on X11.
+    Primary,
+}
+
+/// A DEC private mode of the terminal, whose status can be queried with
+/// [Value::ModeStatus](enum.Value.html#variant.ModeStatus).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum Mode {
+    /// Automatic line wrapping (7).
+    LineWrap,
+    /// The visibility of the cursor (25).
+    CursorVisible,
+    /// Mouse button and drag reporting (1002).
+    MouseCapture,
+    /// Focus change reporting (1004).
+    FocusChange,
+    /// The SGR mouse encoding (1006).
+    SgrMouse,
+    /// The alternate screen (1049).
+    AlternateScreen,
+    /// Bracketed paste (2004).
+    BracketedPaste,
+    /// Synchronized updates (2026).
+    SynchronizedUpdate,
+    /// Any other DEC private mode, by its number.
+    Other(u16),
+}
+
+/// The status of a [Mode](enum.Mode.html), as the terminal reports it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum ModeStatus {
+    /// The terminal doesn't know the mode.
+    NotRecognized,
+    /// The mode is enabled.
+    Set,
+    /// The mode is disabled.
+    Reset,
+    /// The mode is always enabled and can't be changed.
+    PermanentlySet,
+    /// The mode is always disabled and can't be changed.
+    PermanentlyReset,
+}
+
+bitflags! {
+    /// Represents how the terminal processes input and output, a finer grained alternative to raw mode.
+    ///
+    /// Raw mode clears all flags. Cbreak mode, which reads single keys but still raises `SIGINT`
+    /// on Ctrl + C, is `InputMode::SIGNALS | InputMode::OUTPUT_PROCESSING`.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct InputMode: u8 {
+        /// Typed characters are echoed (`ECHO`).
+        const ECHO = 0b0000_0001;
+        /// Input is read per line, which can be edited before Enter is pressed (`ICANON`).
+        const CANONICAL = 0b0000_0010;
+        /// Ctrl + C, Ctrl + Z and Ctrl + \ raise signals instead of being read as keys (`ISIG`).
+        const SIGNALS = 0b0000_0100;
+        /// Output is post-processed, e.g. a new line also returns the cursor to the first column (`OPOST`).
+        const OUTPUT_PROCESSING = 0b0000_1000;
+    }
+}
+
+/// A terminal multiplexer, that runs between the application and the terminal.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum Multiplexer {
+    /// [tmux](https://github.com/tmux/tmux).
+    Tmux,
+    /// [GNU Screen](https://www.gnu.org/software/screen/).
+    Screen,
+    /// [Zellij](https://zellij.dev/).
+    Zellij,
+}
+
+/// The environment that the application runs in, detected from environment variables.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Environment {
+    /// The terminal type from `TERM`, e.g. `xterm-256color`.
+    pub term: Option<String>,
+    /// The terminal program from `TERM_PROGRAM`, e.g. `iTerm.app`, `WezTerm` or `vscode`.
+    pub term_program: Option<String>,
+    /// The multiplexer that the application runs in.
+    ///
+    /// tmux only passes escape sequences it doesn't know to the terminal when they are wrapped.
+    pub multiplexer: Option<Multiplexer>,
+    /// Whether the application runs in an SSH session.
+    pub ssh: bool,
+    /// Whether the application runs in a continuous integration service, where nobody is watching.
+    pub ci: bool,
+    /// Whether the terminal is a dumb terminal (`TERM=dumb`), which doesn't understand escape codes.
+    pub dumb: bool,
+}
+
+/// Whether the input and output of a [Terminal](struct.Terminal.html) are connected to a terminal,
+/// see [Terminal::is_tty](struct.Terminal.html#method.is_tty).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IsTty {
+    /// Whether stdin is a terminal, `false` if input is piped or redirected from a file.
+    pub input: bool,
+    /// Whether the output of the [Terminal](struct.Terminal.html) is a terminal,
+    /// `false` if output is piped or redirected to a file.
+    ///
+    /// `None` if it isn't known, e.g. for a buffer given to
+    /// [Terminal::custom](struct.Terminal.html#method.custom).
+    pub output: Option<bool>,
+}
+
+/// The identity of the terminal, as it reports itself.
+///
+/// Fields are `None` or empty if the terminal doesn't answer the corresponding query.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TerminalIdentity {
+    /// The name of the terminal from XTVERSION, e.g. `XTerm`, `kitty` or `WezTerm`.
+    pub name: Option<String>,
+    /// The version of the terminal from XTVERSION, in the format of the terminal.
+    pub version: Option<String>,
+    /// The terminal type from the secondary device attributes (DA2),
+    /// e.g. 41 for xterm or 65 for VTE based terminals.
+    pub device_type: Option<u16>,
+    /// The firmware version from the secondary device attributes (DA2),
+    /// which is the patch level for xterm and the version for VTE.
+    pub firmware_version: Option<u16>,
+    /// The feature codes from the primary device attributes (DA1), e.g. 4 for sixel graphics.
+    ///
+    /// The first code is the conformance level, e.g. 62 for VT220 or 65 for VT525.
+    pub features: Vec<u16>,
+}
+
+impl TerminalIdentity {
+    /// Returns whether the terminal supports sixel graphics.
+    pub fn supports_sixel(&self) -> bool {
+        self.features.iter().skip(1).any(|feature| *feature == 4)
+    }
+}
@@ -3,7 +3,9 @@
 pub use self::{
     action::{Action, Retrieved, Value},
     enums::{
-        Attribute, Clear, Color, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+        Attribute, Clear, ClipboardSelection, Color, ColorSupport, Environment, Event, InputMode,
+        IsTty, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, Mode,
+        ModeStatus, MouseButton, MouseEvent, Multiplexer, TerminalIdentity,
     },
     terminal::{stderr, stdout, Terminal, TerminalLock},
 };
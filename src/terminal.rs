@@ -1,21 +1,24 @@
 use std::{
-    io::{self, Stderr, Stdout, Write},
-    sync::{RwLock, RwLockWriteGuard},
+    io::{self, IsTerminal, Stderr, Stdout, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock, RwLockWriteGuard,
+    },
 };
 
 use crate::{
     backend::{Backend as _, BackendImpl},
-    error, Action, Retrieved, Value,
+    error, Action, IsTty, Retrieved, Value,
 };
 
 /// Creates a [Stdout](https://doc.rust-lang.org/std/io/struct.Stdout.html) buffered [Terminal](struct.Terminal.html).
 pub fn stdout() -> Terminal<Stdout> {
-    Terminal::custom(io::stdout())
+    Terminal::custom_with_tty(io::stdout(), io::stdout().is_terminal())
 }
 
 /// Creates a [Stderr](https://doc.rust-lang.org/std/io/struct.Stdout.html) buffered [Terminal](struct.Terminal.html).
 pub fn stderr() -> Terminal<Stderr> {
-    Terminal::custom(io::stderr())
+    Terminal::custom_with_tty(io::stderr(), io::stderr().is_terminal())
 }
 
 /// A simple interface to perform operations on the terminal.
@@ -54,23 +57,61 @@ pub struct Terminal<W: Write> {
     // Access to the `Terminal` internals is ONLY allowed if this lock is acquired,
     // use `lock_mut()`.
     lock: RwLock<BackendImpl<W>>,
+    synchronized_flush: SynchronizedFlush,
+    // Whether the buffer is a terminal, if known.
+    buffer_is_tty: Option<bool>,
 }
 
 impl<W: Write> Terminal<W> {
     /// Creates a custom buffered [Terminal](struct.Terminal.html) with the given buffer.
+    ///
+    /// [is_tty](struct.Terminal.html#method.is_tty) can't tell whether the buffer writes to a terminal,
+    /// use [custom_with_tty](struct.Terminal.html#method.custom_with_tty) if it is known.
     pub fn custom(buffer: W) -> Terminal<W> {
         Terminal {
             lock: RwLock::new(BackendImpl::create(buffer)),
+            synchronized_flush: SynchronizedFlush::default(),
+            buffer_is_tty: None,
         }
     }
 
+    /// Creates a custom buffered [Terminal](struct.Terminal.html) with the given buffer,
+    /// `is_tty` tells whether the buffer writes to a terminal.
+    ///
+    /// The buffer can't always be inspected, e.g. if it wraps the actual output.
+    /// See [is_tty](struct.Terminal.html#method.is_tty).
+    pub fn custom_with_tty(buffer: W, is_tty: bool) -> Terminal<W> {
+        Terminal {
+            buffer_is_tty: Some(is_tty),
+            ..Terminal::custom(buffer)
+        }
+    }
+
+    /// Sets whether every flushed batch and every performed action is wrapped in a synchronized update.
+    ///
+    /// Terminals that support synchronized updates present each flushed batch at once,
+    /// which prevents tearing of full screen repaints. Other terminals ignore it.
+    /// See [Action::BeginSynchronizedUpdate](enum.Action.html#variant.BeginSynchronizedUpdate).
+    ///
+    /// Text that is written with the `Write` implementation is only part of an update
+    /// if it is written after batching an action and before flushing the batch.
+    pub fn set_synchronized_flush(&self, enabled: bool) {
+        self.synchronized_flush
+            .enabled
+            .store(enabled, Ordering::SeqCst);
+    }
+
     /// Locks this [Terminal](struct.Terminal.html), returning a mutable lock guard.
     /// A deadlock is not possible, instead an error will be returned if a lock is already in use.
     /// Make sure this lock is only used at one place.
     /// The lock is released when the returned lock goes out of scope.
     pub fn lock_mut(&self) -> error::Result<TerminalLock<'_, W>> {
         if let Ok(lock) = self.lock.try_write() {
-            Ok(TerminalLock::new(lock))
+            Ok(TerminalLock::new(
+                lock,
+                &self.synchronized_flush,
+                self.buffer_is_tty,
+            ))
         } else {
             Err(error::ErrorKind::AttemptToAcquireLock(
                 "`Terminal` can only be mutably borrowed once.".to_string(),
@@ -118,6 +159,25 @@ impl<W: Write> Terminal<W> {
         let lock = self.lock_mut()?;
         lock.get(value)
     }
+
+    /// Returns whether stdin and the output of this [Terminal](struct.Terminal.html) are connected to a terminal.
+    ///
+    /// Escape codes and raw mode are meaningless if the output is piped,
+    /// applications can fall back to plain output instead.
+    ///
+    /// Backends that write to the terminal device themselves (crosscurses, termion in raw mode)
+    /// report their actual output. Otherwise the output is known for [stdout](fn.stdout.html),
+    /// [stderr](fn.stderr.html) and buffers given to
+    /// [custom_with_tty](struct.Terminal.html#method.custom_with_tty), and is `None` for other buffers.
+    ///
+    /// # Note
+    ///
+    /// Acquires an lock for underlying mutability,
+    /// this can be prevented with [lock_mut](struct.Terminal.html#method.lock_mut).
+    pub fn is_tty(&self) -> error::Result<IsTty> {
+        let lock = self.lock_mut()?;
+        Ok(lock.is_tty())
+    }
 }
 
 impl<'a, W: Write> Write for Terminal<W> {
@@ -135,34 +195,71 @@ impl<'a, W: Write> Write for Terminal<W> {
 /// A mutable lock to the [Terminal](struct.Terminal.html).
 pub struct TerminalLock<'a, W: Write> {
     backend: RwLockWriteGuard<'a, BackendImpl<W>>,
+    synchronized_flush: &'a SynchronizedFlush,
+    buffer_is_tty: Option<bool>,
 }
 
 impl<'a, W: Write> TerminalLock<'a, W> {
-    pub fn new(locked_backend: RwLockWriteGuard<'a, BackendImpl<W>>) -> TerminalLock<'a, W> {
+    pub(crate) fn new(
+        locked_backend: RwLockWriteGuard<'a, BackendImpl<W>>,
+        synchronized_flush: &'a SynchronizedFlush,
+        buffer_is_tty: Option<bool>,
+    ) -> TerminalLock<'a, W> {
         TerminalLock {
             backend: locked_backend,
+            synchronized_flush,
+            buffer_is_tty,
         }
     }
 
     /// See [Terminal::act](struct.Terminal.html#method.act).
     pub fn act(&mut self, action: Action) -> error::Result<()> {
-        self.backend.act(action)
+        // Performing an action is the same as batching and flushing it, which handles the synchronized update.
+        self.batch(action)?;
+        self.flush_batch()
     }
 
     /// See [Terminal::batch](struct.Terminal.html#method.batch).
     pub fn batch(&mut self, action: Action) -> error::Result<()> {
+        if action == Action::Flash {
+            // Flashing flushes the batch, the terminal would hold it back until the update ends.
+            if self.synchronized_flush.end_update() {
+                self.backend.batch(Action::EndSynchronizedUpdate)?;
+            }
+
+            return self.backend.batch(action);
+        }
+
+        if self.synchronized_flush.begin_update() {
+            self.backend.batch(Action::BeginSynchronizedUpdate)?;
+        }
+
         self.backend.batch(action)
     }
 
     /// See [Terminal::flush_batch](struct.Terminal.html#method.flush_batch).
     pub fn flush_batch(&mut self) -> error::Result<()> {
-        self.backend.flush_batch()
+        self.backend.flush_batch()?;
+
+        if self.synchronized_flush.end_update() {
+            self.backend.act(Action::EndSynchronizedUpdate)?;
+        }
+
+        Ok(())
     }
 
     /// See [Terminal::get](struct.Terminal.html#method.get).
     pub fn get(&self, value: Value) -> error::Result<Retrieved> {
         self.backend.get(value)
     }
+
+    /// See [Terminal::is_tty](struct.Terminal.html#method.is_tty).
+    pub fn is_tty(&self) -> IsTty {
+        IsTty {
+            input: io::stdin().is_terminal(),
+            output: self.backend.output_is_tty().or(self.buffer_is_tty),
+        }
+    }
 }
 
 impl<'a, W: Write> Write for TerminalLock<'a, W> {
@@ -174,3 +271,104 @@ impl<'a, W: Write> Write for TerminalLock<'a, W> {
         self.backend.flush()
     }
 }
+
+/// Keeps track of the synchronized update that wraps the current batch,
+/// see [Terminal::set_synchronized_flush](struct.Terminal.html#method.set_synchronized_flush).
+#[derive(Default)]
+pub(crate) struct SynchronizedFlush {
+    enabled: AtomicBool,
+    // Whether the update was begun by the first action of the current batch.
+    update_begun: AtomicBool,
+}
+
+impl SynchronizedFlush {
+    /// Returns `true` if a synchronized update has to be begun before the next batched action.
+    fn begin_update(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst) && !self.update_begun.swap(true, Ordering::SeqCst)
+    }
+
+    /// Returns `true` if a synchronized update has to be ended after flushing the batch.
+    fn end_update(&self) -> bool {
+        self.update_begun.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[cfg(all(test, feature = "crossterm-backend"))]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use super::Terminal;
+    use crate::Action;
+
+    /// A buffer whose content can still be read after it was given to a terminal.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn output(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_synchronized_flush() {
+        let buffer = SharedBuffer::default();
+        let terminal = Terminal::custom(buffer.clone());
+        terminal.set_synchronized_flush(true);
+
+        terminal.batch(Action::MoveCursorTo(0, 0)).unwrap();
+        terminal.batch(Action::HideCursor).unwrap();
+        terminal.flush_batch().unwrap();
+        terminal.act(Action::ShowCursor).unwrap();
+
+        assert_eq!(
+            buffer.output(),
+            "\x1B[?2026h\x1B[1;1H\x1B[?25l\x1B[?2026l\x1B[?2026h\x1B[?25h\x1B[?2026l"
+        );
+
+        terminal.set_synchronized_flush(false);
+        terminal.act(Action::HideCursor).unwrap();
+
+        assert!(buffer.output().ends_with("\x1B[?2026l\x1B[?25l"));
+    }
+
+    #[test]
+    fn test_is_tty() {
+        for is_tty in &[true, false] {
+            let terminal = Terminal::custom_with_tty(Vec::new(), *is_tty);
+            assert_eq!(terminal.is_tty().unwrap().output, Some(*is_tty));
+        }
+
+        let terminal = Terminal::custom(Vec::new());
+        assert_eq!(terminal.is_tty().unwrap().output, None);
+    }
+
+    #[test]
+    fn test_synchronized_flush_flash() {
+        let buffer = SharedBuffer::default();
+        let terminal = Terminal::custom(buffer.clone());
+        terminal.set_synchronized_flush(true);
+
+        // The update has to end before the screen is flashed, otherwise the flash isn't shown.
+        terminal.batch(Action::MoveCursorTo(0, 0)).unwrap();
+        terminal.batch(Action::Flash).unwrap();
+
+        assert_eq!(
+            buffer.output(),
+            "\x1B[?2026h\x1B[1;1H\x1B[?2026l\x1B[?5h\x1B[?5l"
+        );
+    }
+}